@@ -0,0 +1,155 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::errors::TBError;
+
+/// The shape of a bomb's blast.
+///
+/// A blast travels out from the bomb along each of the pattern's rays, one step at a time, and
+/// destroys every target it passes over as well as anything on the bomb's own cell. Rays have to
+/// come in opposite pairs, so that a bomb hits a target exactly when a bomb on the target's cell
+/// would hit the bomb's cell.
+pub trait AttackPattern: fmt::Debug + Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Direction of each ray as an `(x, y)` step.
+    fn rays(&self) -> &'static [(isize, isize)];
+
+    /// How many steps the blast travels along each ray, or `None` to go all the way to the edge
+    /// of the board.
+    fn reach(&self) -> Option<usize> {
+        None
+    }
+}
+
+const ORTHOGONAL: &[(isize, isize)] = &[(1, 0), (-1, 0), (0, 1), (0, -1)];
+const DIAGONAL: &[(isize, isize)] = &[(1, 1), (-1, -1), (1, -1), (-1, 1)];
+const ALL_DIRECTIONS: &[(isize, isize)] = &[
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+];
+// In axial coordinates, see Grid::Hex
+const HEX_AXES: &[(isize, isize)] = &[(1, 0), (-1, 0), (0, 1), (0, -1), (1, -1), (-1, 1)];
+const KNIGHT_JUMPS: &[(isize, isize)] = &[
+    (1, 2),
+    (-1, -2),
+    (2, 1),
+    (-2, -1),
+    (2, -1),
+    (-2, 1),
+    (1, -2),
+    (-1, 2),
+];
+
+/// Whole row, column and both diagonals, the classic rules.
+#[derive(Debug, Clone, Copy)]
+pub struct Queen;
+
+impl AttackPattern for Queen {
+    fn name(&self) -> &'static str {
+        "queen"
+    }
+
+    fn rays(&self) -> &'static [(isize, isize)] {
+        ALL_DIRECTIONS
+    }
+}
+
+/// Whole row and column only.
+#[derive(Debug, Clone, Copy)]
+pub struct Rook;
+
+impl AttackPattern for Rook {
+    fn name(&self) -> &'static str {
+        "rook"
+    }
+
+    fn rays(&self) -> &'static [(isize, isize)] {
+        ORTHOGONAL
+    }
+}
+
+/// Both diagonals only.
+#[derive(Debug, Clone, Copy)]
+pub struct Bishop;
+
+impl AttackPattern for Bishop {
+    fn name(&self) -> &'static str {
+        "bishop"
+    }
+
+    fn rays(&self) -> &'static [(isize, isize)] {
+        DIAGONAL
+    }
+}
+
+/// The eight neighbouring cells.
+#[derive(Debug, Clone, Copy)]
+pub struct King;
+
+impl AttackPattern for King {
+    fn name(&self) -> &'static str {
+        "king"
+    }
+
+    fn rays(&self) -> &'static [(isize, isize)] {
+        ALL_DIRECTIONS
+    }
+
+    fn reach(&self) -> Option<usize> {
+        Some(1)
+    }
+}
+
+/// The eight cells a knight's move away.
+#[derive(Debug, Clone, Copy)]
+pub struct Knight;
+
+impl AttackPattern for Knight {
+    fn name(&self) -> &'static str {
+        "knight"
+    }
+
+    fn rays(&self) -> &'static [(isize, isize)] {
+        KNIGHT_JUMPS
+    }
+
+    fn reach(&self) -> Option<usize> {
+        Some(1)
+    }
+}
+
+/// Whole lines along the three axes of a hex grid. Only makes sense on a hex board, so it isn't
+/// one of the named patterns.
+#[derive(Debug, Clone, Copy)]
+pub struct HexLines;
+
+impl AttackPattern for HexLines {
+    fn name(&self) -> &'static str {
+        "hex"
+    }
+
+    fn rays(&self) -> &'static [(isize, isize)] {
+        HEX_AXES
+    }
+}
+
+pub const PATTERN_NAMES: &[&str] = &["queen", "rook", "bishop", "king", "knight"];
+
+/// Looks up one of the built-in patterns by name.
+pub fn pattern_by_name(name: &str) -> Result<Arc<dyn AttackPattern>, TBError> {
+    match name {
+        "queen" => Ok(Arc::new(Queen)),
+        "rook" => Ok(Arc::new(Rook)),
+        "bishop" => Ok(Arc::new(Bishop)),
+        "king" => Ok(Arc::new(King)),
+        "knight" => Ok(Arc::new(Knight)),
+        _ => Err(TBError::UnknownPattern(name.to_string())),
+    }
+}
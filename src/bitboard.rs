@@ -0,0 +1,236 @@
+use std::collections::HashSet;
+use std::ops::BitOr;
+
+use crate::coord::Coord;
+use crate::geometry::Geometry;
+use crate::symmetry::{self, choose_pivot, find_symmetries, Symmetry};
+
+/// A set of targets, one bit per target index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TargetBits {
+    words: Vec<u64>,
+}
+
+impl TargetBits {
+    pub fn new(len: usize) -> Self {
+        TargetBits {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    pub fn insert(&mut self, index: usize) {
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Number of targets in the set.
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    /// The target indices in the set, lowest first.
+    pub fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| i * 64 + bit)
+        })
+    }
+
+    pub fn is_subset(&self, other: &TargetBits) -> bool {
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .all(|(word, other_word)| word & !other_word == 0)
+    }
+
+    /// Returns the lowest target index that is in `other` but not in `self`.
+    pub fn first_missing(&self, other: &TargetBits) -> Option<usize> {
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .enumerate()
+            .find(|(_, (word, other_word))| **other_word & !**word != 0)
+            .map(|(i, (word, other_word))| i * 64 + (other_word & !word).trailing_zeros() as usize)
+    }
+}
+
+impl<'a> BitOr for &'a TargetBits {
+    type Output = TargetBits;
+
+    fn bitor(self, other: &'a TargetBits) -> TargetBits {
+        TargetBits {
+            words: self
+                .words
+                .iter()
+                .zip(other.words.iter())
+                .map(|(word, other_word)| word | other_word)
+                .collect(),
+        }
+    }
+}
+
+/// Solver backend that works out up front which targets a bomb on each cell would hit, as one
+/// bitset per cell, so checking a candidate costs a few word-wise ORs instead of a pass over every
+/// target.
+pub struct BitBoard {
+    geometry: Geometry,
+    targets: Vec<Coord>,
+    all: TargetBits,
+    // indexed by Geometry::index
+    masks: Vec<TargetBits>,
+    /// Symmetries of the board that map the targets onto themselves
+    symmetries: Vec<Symmetry>,
+    /// Index of the target every solution's first bomb has to hit
+    pivot: usize,
+}
+
+impl BitBoard {
+    pub fn new(targets: &HashSet<Coord>, geometry: &Geometry) -> Self {
+        // Index the targets in row-major order, so nothing picked by index depends on hash order
+        let mut target_list: Vec<Coord> = targets.iter().cloned().collect();
+        target_list.sort();
+        let empty = TargetBits::new(target_list.len());
+        let symmetries = find_symmetries(targets, geometry);
+        let pivot = choose_pivot(targets, geometry, &symmetries)
+            .and_then(|pivot| target_list.binary_search(&pivot).ok())
+            .unwrap_or(0);
+        let mut bitboard = BitBoard {
+            geometry: geometry.clone(),
+            all: empty.clone(),
+            masks: vec![empty; geometry.width * geometry.height],
+            symmetries,
+            pivot,
+            targets: target_list,
+        };
+        for (index, target) in bitboard.targets.iter().enumerate() {
+            bitboard.all.insert(index);
+            for cell in geometry.attackers(target) {
+                bitboard.masks[geometry.index(&cell)].insert(index);
+            }
+        }
+        bitboard
+    }
+
+    /// The set of targets a bomb at `bomb` destroys.
+    pub fn hit_mask(&self, bomb: &Coord) -> &TargetBits {
+        &self.masks[self.geometry.index(bomb)]
+    }
+
+    /// The first target, in index order, that isn't in `hit`.
+    pub fn first_missed(&self, hit: &TargetBits) -> Option<Coord> {
+        hit.first_missing(&self.all).map(|index| self.targets[index])
+    }
+
+    /// Every target, in index order, that isn't in `hit`.
+    pub fn missed(&self, hit: &TargetBits) -> Vec<Coord> {
+        (0..self.targets.len())
+            .filter(|index| !hit.contains(*index))
+            .map(|index| self.targets[index])
+            .collect()
+    }
+
+    pub fn target_count(&self) -> usize {
+        self.targets.len()
+    }
+
+    fn attack_points(&self, target: usize) -> Vec<Coord> {
+        self.geometry.attackers(&self.targets[target])
+    }
+
+    /// Cells worth trying as the second bomb after one at `b1`.
+    pub fn second_bomb_candidates(&self, b1: &Coord) -> Vec<Coord> {
+        match self.hit_mask(b1).first_missing(&self.all) {
+            // The first bomb cleared everything by itself, so the second may go anywhere
+            None => self.geometry.cells().collect(),
+            // Only cells that hit the first target left standing can finish the job
+            Some(missed) => self.attack_points(missed),
+        }
+    }
+
+    /// Whether bombs at `b1` and `b2` clear every target between them.
+    pub fn is_solution(&self, b1: &Coord, b2: &Coord) -> bool {
+        b1 != b2 && self.hit_mask(b1) | self.hit_mask(b2) == self.all
+    }
+
+    /// Every cell that completes a solution together with a bomb at `b1`.
+    pub fn second_bombs(&self, b1: &Coord) -> Vec<Coord> {
+        self.second_bomb_candidates(b1)
+            .into_iter()
+            .filter(|b2| self.is_solution(b1, b2))
+            .collect()
+    }
+
+    /// Whether the pair `(b1, b2)` would also be found the other way around, with `b2` as the
+    /// first bomb, and isn't the ordering that gets reported.
+    pub fn is_duplicate(&self, b1: &Coord, b2: &Coord) -> bool {
+        self.hit_mask(b2).contains(self.pivot) && (b2.y, b2.x) < (b1.y, b1.x)
+    }
+
+    /// Candidates for the first bomb, in row-major order: one bomb of every solution hits the
+    /// pivot target.
+    pub fn first_bombs(&self) -> Vec<Coord> {
+        if self.targets.is_empty() {
+            return Vec::new();
+        }
+        let mut first_bombs = self.attack_points(self.pivot);
+        first_bombs.sort();
+        first_bombs
+    }
+
+    /// The first bombs left once symmetric candidates are pruned to one per orbit. Every solution
+    /// is the image of one starting with these under a symmetry, which `expand_solutions` undoes.
+    pub fn first_bomb_representatives(&self) -> Vec<Coord> {
+        self.first_bombs()
+            .into_iter()
+            .filter(|b1| {
+                symmetry::is_representative(
+                    b1,
+                    &self.targets[self.pivot],
+                    &self.symmetries,
+                    &self.geometry,
+                )
+            })
+            .collect()
+    }
+
+    /// Maps solutions found from `first_bomb_representatives` through every symmetry, giving each
+    /// distinct solution once.
+    ///
+    /// Solutions come in row-major order of the first bomb, then of the second. The first bomb of
+    /// each pair is the one hitting the pivot target, or the earlier one if both do.
+    pub fn expand_solutions(&self, found: &[(Coord, Coord)]) -> Vec<(Coord, Coord)> {
+        let mut solutions: Vec<(Coord, Coord)> = found
+            .iter()
+            .flat_map(|(b1, b2)| {
+                self.symmetries.iter().map(move |symmetry| {
+                    let b1 = symmetry.apply(b1, &self.geometry);
+                    let b2 = symmetry.apply(b2, &self.geometry);
+                    if self.hit_mask(&b1).contains(self.pivot) && !self.is_duplicate(&b1, &b2) {
+                        (b1, b2)
+                    } else {
+                        (b2, b1)
+                    }
+                })
+            })
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        solutions.sort();
+        solutions
+    }
+
+    /// The first solution in the order `expand_solutions` lists them, if there is one.
+    pub fn find_bombs(&self) -> Option<(Coord, Coord)> {
+        self.first_bomb_representatives()
+            .into_iter()
+            .find_map(|b1| self.second_bombs(&b1).into_iter().min().map(|b2| (b1, b2)))
+    }
+}
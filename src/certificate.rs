@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::bitboard::BitBoard;
+use crate::coord::Coord;
+use crate::geometry::Geometry;
+
+/// Every second bomb that could follow a given first bomb, each paired with a target that both
+/// bombs miss.
+#[derive(Debug, Clone)]
+pub struct Refutation {
+    pub b1: Coord,
+    /// A target the first bomb misses, so the second bomb has to hit it
+    pub missed: Coord,
+    pub b2_misses: Vec<(Coord, Coord)>,
+}
+
+/// An independently checkable reason why no two bombs clear a set of targets.
+#[derive(Debug, Clone)]
+pub enum Certificate {
+    /// Three targets no single bomb reaches more than one of
+    SpreadTargets([Coord; 3]),
+    /// One bomb has to hit `pivot`, and every cell that does is refuted
+    Exhaustive {
+        pivot: Coord,
+        refutations: Vec<Refutation>,
+    },
+}
+
+impl Certificate {
+    /// Number of bomb pairs the certificate rules out one by one.
+    pub fn cases(&self) -> usize {
+        match self {
+            Certificate::SpreadTargets(_) => 0,
+            Certificate::Exhaustive { refutations, .. } => refutations
+                .iter()
+                .map(|refutation| refutation.b2_misses.len())
+                .sum(),
+        }
+    }
+}
+
+impl fmt::Display for Certificate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Certificate::SpreadTargets([a, b, c]) => write!(
+                f,
+                "No single bomb reaches more than one of the targets at {}; {} and {}.",
+                a, b, c
+            ),
+            Certificate::Exhaustive { pivot, refutations } => write!(
+                f,
+                "Each of the {} cells hitting {} leaves a target that no second bomb can \
+                 finish off ({} bomb pairs ruled out).",
+                refutations.len(),
+                pivot,
+                self.cases()
+            ),
+        }
+    }
+}
+
+fn attack_points(target: &Coord, geometry: &Geometry) -> HashSet<Coord> {
+    geometry.attackers(target).into_iter().collect()
+}
+
+/// Cells hitting `target`, in row-major order.
+fn sorted_attack_points(target: &Coord, geometry: &Geometry) -> Vec<Coord> {
+    let mut cells = geometry.attackers(target);
+    cells.sort();
+    cells
+}
+
+fn find_spread_targets(targets: &HashSet<Coord>, geometry: &Geometry) -> Option<[Coord; 3]> {
+    let mut targets: Vec<(Coord, HashSet<Coord>)> = targets
+        .iter()
+        .map(|target| (*target, attack_points(target, geometry)))
+        .collect();
+    targets.sort_by_key(|(target, _)| *target);
+    let apart = |a: usize, b: usize| targets[a].1.is_disjoint(&targets[b].1);
+    for a in 0..targets.len() {
+        for b in (a + 1)..targets.len() {
+            if !apart(a, b) {
+                continue;
+            }
+            for c in (b + 1)..targets.len() {
+                if apart(a, c) && apart(b, c) {
+                    return Some([targets[a].0, targets[b].0, targets[c].0]);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn find_exhaustive(targets: &HashSet<Coord>, geometry: &Geometry) -> Option<Certificate> {
+    let bitboard = BitBoard::new(targets, geometry);
+    let pivot = *targets.iter().min()?;
+    let mut refutations = Vec::new();
+    for b1 in sorted_attack_points(&pivot, geometry) {
+        let b1_mask = bitboard.hit_mask(&b1);
+        // A bomb that clears everything on its own means the board is solvable after all
+        let missed = bitboard.first_missed(b1_mask)?;
+        let mut b2_misses = Vec::new();
+        for b2 in sorted_attack_points(&missed, geometry) {
+            let also_missed = bitboard.first_missed(&(b1_mask | bitboard.hit_mask(&b2)))?;
+            b2_misses.push((b2, also_missed));
+        }
+        refutations.push(Refutation {
+            b1,
+            missed,
+            b2_misses,
+        });
+    }
+    Some(Certificate::Exhaustive { pivot, refutations })
+}
+
+/// Builds a certificate that no two bombs clear `targets`, or `None` if some pair does.
+pub fn find_certificate(targets: &HashSet<Coord>, geometry: &Geometry) -> Option<Certificate> {
+    match find_spread_targets(targets, geometry) {
+        Some(spread) => Some(Certificate::SpreadTargets(spread)),
+        None => find_exhaustive(targets, geometry),
+    }
+}
+
+/// Re-checks `certificate` against `targets` using nothing but the blast geometry.
+pub fn verify_certificate(targets: &HashSet<Coord>, geometry: &Geometry, certificate: &Certificate) -> bool {
+    match certificate {
+        Certificate::SpreadTargets(spread) => {
+            spread.iter().all(|target| targets.contains(target))
+                && geometry.cells().all(|bomb| {
+                    spread
+                        .iter()
+                        .filter(|target| geometry.hits(&bomb, target))
+                        .count()
+                        <= 1
+                })
+        }
+        Certificate::Exhaustive { pivot, refutations } => {
+            let b1s: HashSet<Coord> = refutations.iter().map(|refutation| refutation.b1).collect();
+            targets.contains(pivot)
+                && b1s == attack_points(pivot, geometry)
+                && refutations.iter().all(|refutation| {
+                    let b2s: HashSet<Coord> =
+                        refutation.b2_misses.iter().map(|(b2, _)| *b2).collect();
+                    targets.contains(&refutation.missed)
+                        && !geometry.hits(&refutation.b1, &refutation.missed)
+                        && b2s == attack_points(&refutation.missed, geometry)
+                        && refutation.b2_misses.iter().all(|(b2, target)| {
+                            targets.contains(target)
+                                && !geometry.hits(&refutation.b1, target)
+                                && !geometry.hits(b2, target)
+                        })
+                })
+        }
+    }
+}
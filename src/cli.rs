@@ -0,0 +1,407 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use anyhow::{ensure, Context, Result};
+use clap::ArgMatches;
+
+use twobombs::attack::pattern_by_name;
+use twobombs::bitboard::BitBoard;
+use twobombs::certificate::{verify_certificate, Certificate};
+use twobombs::control::{no_progress, CancelToken};
+use twobombs::coord::Coord;
+use twobombs::daily::{self, Stats};
+use twobombs::dimacs::Cnf;
+use twobombs::errors::TBError;
+use twobombs::generate::{self, PuzzleSpec};
+use twobombs::geometric::{parse_points, LineBoard};
+use twobombs::parallel;
+use twobombs::rng::{self, Rng};
+use twobombs::geometry::{Geometry, Topology};
+use twobombs::twobombs::{TBBoard, TBGame};
+
+/// Builds the geometry for a board of the given size from the command line's rule options.
+fn geometry(matches: &ArgMatches, width: usize, height: usize) -> Result<Geometry> {
+    let radius = match matches.value_of("radius") {
+        Some(radius) => Some(radius.parse()?),
+        None => None,
+    };
+    let topology = if matches.is_present("torus") {
+        Topology::Torus
+    } else {
+        Topology::Plane
+    };
+    let geometry = if matches.is_present("hex") {
+        // Odd rows are shifted, so wrapping from the last row back to the first only lines up
+        // when there's an even number of them
+        if topology == Topology::Torus && !height.is_multiple_of(2) {
+            return Err(TBError::BadBoard(String::from(
+                "a hex board can only wrap around with an even number of rows",
+            ))
+            .into());
+        }
+        Geometry::hex(width, height)
+    } else {
+        Geometry::new(width, height, pattern_by_name(matches.value_of("pattern").unwrap_or("queen"))?)
+    };
+    Ok(geometry.with_radius(radius).with_topology(topology))
+}
+
+fn read_board_file(matches: &ArgMatches, path: &str) -> Result<TBBoard> {
+    let text = fs::read_to_string(path)?;
+    let mut board: TBBoard = text.parse()?;
+    board.set_geometry(geometry(matches, board.get_width(), board.get_height())?);
+    Ok(board)
+}
+
+fn read_board(matches: &ArgMatches) -> Result<TBBoard> {
+    read_board_file(matches, matches.value_of("BOARD").unwrap_or_default())
+}
+
+/// Sets up a new interactive game from the command line options.
+pub fn new_game(matches: &ArgMatches) -> Result<TBGame> {
+    let width = matches.value_of("width").unwrap_or("23").parse()?;
+    let height = matches.value_of("height").unwrap_or("23").parse()?;
+    Ok(TBGame::new_with_geometry(geometry(matches, width, height)?))
+}
+
+pub fn threads(matches: &ArgMatches) -> Result<usize> {
+    match matches.value_of("threads") {
+        Some("0") | None => Ok(parallel::default_threads()),
+        Some(threads) => Ok(threads.parse()?),
+    }
+}
+
+pub fn timeout(matches: &ArgMatches) -> Result<Option<Duration>> {
+    match matches.value_of("timeout") {
+        Some(timeout) => Ok(Some(
+            Duration::try_from_secs_f64(timeout.parse()?)
+                .with_context(|| format!("invalid timeout '{}'", timeout))?,
+        )),
+        None => Ok(None),
+    }
+}
+
+pub fn solve(matches: &ArgMatches) -> Result<()> {
+    let mut board = read_board(matches)?;
+    let threads = threads(matches)?;
+    let cancel = CancelToken::new().with_timeout(timeout(matches)?);
+
+    if let Some(max_bombs) = matches.value_of("max-bombs") {
+        let max_bombs: usize = max_bombs.parse()?;
+        let bombs = board.find_min_bombs(max_bombs, &cancel);
+        if cancel.is_cancelled() {
+            return Err(TBError::TimedOut.into());
+        }
+        if let Some(bombs) = bombs {
+            let bombs: Vec<String> = bombs.iter().map(|bomb| bomb.to_string()).collect();
+            println!("{} bombs needed, at {}", bombs.len(), bombs.join(" "));
+            print!("{}", board);
+        } else {
+            println!("No solution found with up to {} bombs!", max_bombs);
+            println!("{}", board.find_max_coverage(max_bombs));
+            print!("{}", board);
+        }
+    } else {
+        let all = matches.is_present("all");
+        let result = if all {
+            board.find_all_bombs(threads, &cancel, &no_progress)
+        } else {
+            board.find_bombs(threads, &cancel, &no_progress)
+        };
+        if result.cancelled {
+            if matches.is_present("stats") {
+                println!("{}", result);
+            }
+            return Err(TBError::TimedOut.into());
+        }
+        if all {
+            for (count, (b1, b2)) in result.solutions.iter().enumerate() {
+                println!("{}: {} {}", count + 1, b1, b2);
+            }
+            println!("{} solutions found", result.solutions.len());
+        } else if let Some((b1, b2)) = result.solution() {
+            println!("Solution found! Bombs at {} and {}", b1, b2);
+            print!("{}", board);
+        } else {
+            println!("No solution found!");
+        }
+        if result.solutions.is_empty() {
+            if let Some(certificate) = board.find_certificate() {
+                let verified = verify_certificate(&board.targets(), &board.geometry(), &certificate);
+                println!("{}", certificate);
+                println!("Certificate {}", if verified { "verified" } else { "FAILED verification" });
+                if matches.is_present("certificate") {
+                    print_certificate(&certificate);
+                }
+            }
+            // Show how close two bombs get, with the targets left standing marked '!'
+            println!("{}", board.find_max_coverage(2));
+            print!("{}", board);
+        }
+        if matches.is_present("stats") {
+            println!("{}", result);
+        }
+    }
+    Ok(())
+}
+
+fn print_certificate(certificate: &Certificate) {
+    match certificate {
+        Certificate::SpreadTargets(spread) => {
+            println!("spread {} {} {}", spread[0], spread[1], spread[2]);
+        }
+        Certificate::Exhaustive { pivot, refutations } => {
+            println!("pivot {}", pivot);
+            for refutation in refutations {
+                println!("  bomb {} misses {}", refutation.b1, refutation.missed);
+                for (b2, target) in &refutation.b2_misses {
+                    println!("    with bomb {} both miss {}", b2, target);
+                }
+            }
+        }
+    }
+}
+
+pub fn cnf(matches: &ArgMatches) -> Result<()> {
+    let board = read_board(matches)?;
+    let bomb_count = matches.value_of("bombs").unwrap_or("2").parse()?;
+    let cnf = Cnf::new(&board.targets(), &board.geometry(), bomb_count);
+    match matches.value_of("output") {
+        Some(output) => fs::write(output, cnf.to_string())?,
+        None => print!("{}", cnf),
+    }
+    Ok(())
+}
+
+pub fn model(matches: &ArgMatches) -> Result<()> {
+    let mut board = read_board(matches)?;
+    let bomb_count = matches.value_of("bombs").unwrap_or("2").parse()?;
+    let model = fs::read_to_string(matches.value_of("MODEL").unwrap_or_default())?;
+    let (targets, geometry) = (board.targets(), board.geometry());
+    let cnf = Cnf::new(&targets, &geometry, bomb_count);
+    match cnf.read_model(&model, &targets, &geometry)? {
+        Some(bombs) => {
+            let coords: Vec<String> = bombs.iter().map(|bomb| bomb.to_string()).collect();
+            println!("Model verified! Bombs at {}", coords.join(" "));
+            board.place_bombs(&bombs);
+            print!("{}", board);
+        }
+        None => println!("The SAT solver found no placement of up to {} bombs.", bomb_count),
+    }
+    Ok(())
+}
+
+pub fn generate(matches: &ArgMatches) -> Result<()> {
+    let width = matches.value_of("width").unwrap_or("23").parse()?;
+    let height = matches.value_of("height").unwrap_or("23").parse()?;
+    let spec = PuzzleSpec {
+        target_count: matches.value_of("targets").unwrap_or("8").parse()?,
+        bomb_count: matches.value_of("bombs").unwrap_or("2").parse()?,
+        unique: matches.is_present("unique"),
+    };
+    let seed = match matches.value_of("seed") {
+        Some(seed) => seed.parse()?,
+        None => rng::clock_seed(),
+    };
+    let geometry = geometry(matches, width, height)?;
+    // A unique board may need more targets than asked for to rule out every other pair
+    let targets = if spec.unique && spec.bomb_count == 2 {
+        generate::generate_unique(&geometry, spec.target_count, seed)?
+    } else {
+        generate::generate(&geometry, &spec, seed)?
+    };
+    let mut board = TBBoard::new_with_geometry(geometry.clone());
+    board.set_targets(&targets);
+    // Keep standard output for the board itself
+    eprintln!("Generated with seed {}", seed);
+    match matches.value_of("output") {
+        Some(output) => fs::write(output, board.to_text())?,
+        None => print!("{}", board.to_text()),
+    }
+    Ok(())
+}
+
+pub fn minimize(matches: &ArgMatches) -> Result<()> {
+    let mut board = read_board(matches)?;
+    let target_count = board.targets().len();
+    let cancel = CancelToken::new().with_timeout(timeout(matches)?);
+    let removed = board.minimize(threads(matches)?, &cancel, &no_progress)?;
+    // Keep standard output for the board itself
+    eprintln!("Removed {} of {} targets", removed, target_count);
+    match matches.value_of("output") {
+        Some(output) => fs::write(output, board.to_text())?,
+        None => print!("{}", board.to_text()),
+    }
+    Ok(())
+}
+
+/// Sets up the daily puzzle, or returns `None` once it's been printed or the history shown.
+pub fn daily(matches: &ArgMatches) -> Result<Option<TBGame>> {
+    let date = match matches.value_of("date") {
+        Some(date) => date.parse()?,
+        None => daily::today(),
+    };
+    if matches.is_present("print") {
+        let mut board = TBBoard::new_with_geometry(daily::geometry());
+        board.set_targets(&daily::puzzle(date)?);
+        // Keep standard output for the board itself
+        eprintln!("Daily puzzle for {}", date);
+        print!("{}", board.to_text());
+        return Ok(None);
+    }
+    let path = match matches.value_of("stats-file") {
+        Some(path) => PathBuf::from(path),
+        None => daily::default_stats_path(),
+    };
+    let stats = Stats::load(&path)?;
+    if matches.is_present("history") {
+        println!("{}", stats);
+        return Ok(None);
+    }
+    Ok(Some(TBGame::new_daily(date, stats)?))
+}
+
+pub fn rate(matches: &ArgMatches) -> Result<()> {
+    let threads = threads(matches)?;
+    for path in matches.values_of("BOARD").into_iter().flatten() {
+        let board = read_board_file(matches, path)?;
+        match board.rate(threads) {
+            Some(rating) => {
+                println!("{}: {}", path, rating);
+                for line in rating.breakdown().lines() {
+                    println!("    {}", line);
+                }
+            }
+            None => println!("{}: not solvable with two bombs", path),
+        }
+    }
+    Ok(())
+}
+
+pub fn sparse(matches: &ArgMatches) -> Result<()> {
+    if matches.is_present("hex") || matches.is_present("torus") || matches.is_present("radius") {
+        return Err(TBError::BadBoard(String::from(
+            "sparse boards are always square grids with no wrapping or blast radius",
+        ))
+        .into());
+    }
+    let bounds = match (matches.value_of("width"), matches.value_of("height")) {
+        (Some(width), Some(height)) => Some((width.parse()?, height.parse()?)),
+        _ => None,
+    };
+    let pattern = pattern_by_name(matches.value_of("pattern").unwrap_or("queen"))?;
+    let board = LineBoard::new(pattern.as_ref(), bounds)?;
+    let text = fs::read_to_string(matches.value_of("TARGETS").unwrap_or_default())?;
+    let targets = parse_points(&text)?;
+    if let Some(outside) = targets.iter().find(|target| !board.contains(target)) {
+        return Err(TBError::BadBoard(format!("target {} is off the board", outside)).into());
+    }
+
+    let cancel = CancelToken::new().with_timeout(timeout(matches)?);
+    let (solution, elapsed) = time(|| board.find_bombs(&targets, &cancel));
+    if cancel.is_cancelled() {
+        return Err(TBError::TimedOut.into());
+    }
+    match solution {
+        Some((b1, b2)) => println!("Solution found! Bombs at {} and {}", b1, b2),
+        None => println!("No solution found!"),
+    }
+    println!("Searched {} targets in {:.3?}", targets.len(), elapsed);
+    Ok(())
+}
+
+fn random_targets(rng: &mut Rng, size: usize, target_count: usize) -> HashSet<Coord> {
+    let mut targets = HashSet::new();
+    while targets.len() < target_count.min(size * size) {
+        targets.insert(Coord {
+            x: rng.below(size),
+            y: rng.below(size),
+        });
+    }
+    targets
+}
+
+fn time<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+pub fn bench(matches: &ArgMatches) -> Result<()> {
+    let target_count: usize = matches.value_of("targets").unwrap_or("20").parse()?;
+    let rounds: usize = matches.value_of("rounds").unwrap_or("5").parse()?;
+    let seed = matches.value_of("seed").unwrap_or("0").parse()?;
+    let mut rng = Rng::new(seed);
+    let threads = threads(matches)?;
+    let sizes: Vec<usize> = match matches.values_of("size") {
+        Some(sizes) => sizes.map(|size| size.parse()).collect::<Result<_, _>>()?,
+        None => vec![23, 50, 100],
+    };
+
+    println!(
+        "{:>6} {:>8} {:>8} {:>14} {:>14} {:>9} {:>14} {:>9}",
+        "size", "targets", "solved", "find_bombs", "bitboard", "speedup", "threaded", "speedup"
+    );
+    for size in sizes {
+        let mut solved = 0;
+        let mut find_bombs_time = Duration::default();
+        let mut bitboard_time = Duration::default();
+        let mut threaded_time = Duration::default();
+        let geometry = Geometry::queen(size, size);
+        for round in 0..rounds {
+            // Random targets are hardly ever solvable, so every other board has a pair of bombs
+            // planted on it to time the solvers finding a solution too
+            let targets = if round % 2 == 0 {
+                let spec = PuzzleSpec {
+                    target_count,
+                    bomb_count: 2,
+                    unique: false,
+                };
+                generate::generate(&geometry, &spec, rng.next_u64())?
+            } else {
+                random_targets(&mut rng, size, target_count)
+            };
+            let (expected, elapsed) = time(|| twobombs::find_bombs(&targets, &geometry));
+            find_bombs_time += elapsed;
+            let (found, elapsed) = time(|| BitBoard::new(&targets, &geometry).find_bombs());
+            bitboard_time += elapsed;
+            let (result, elapsed) = time(|| {
+                parallel::find_bombs(
+                    &BitBoard::new(&targets, &geometry),
+                    threads,
+                    &CancelToken::new(),
+                    &no_progress,
+                )
+            });
+            threaded_time += elapsed;
+            // Every solver lists solutions in the same order, so they should all find the same one
+            ensure!(
+                found == expected && result.solution() == expected,
+                "solvers disagree on size {} round {} with seed {}: {:?}, {:?} and {:?}",
+                size,
+                round,
+                seed,
+                expected,
+                found,
+                result.solution()
+            );
+            if found.is_some() {
+                solved += 1;
+            }
+        }
+        println!(
+            "{:>6} {:>8} {:>8} {:>14?} {:>14?} {:>8.1}x {:>14?} {:>8.1}x",
+            size,
+            target_count,
+            solved,
+            find_bombs_time / rounds as u32,
+            bitboard_time / rounds as u32,
+            find_bombs_time.as_secs_f64() / bitboard_time.as_secs_f64(),
+            threaded_time / rounds as u32,
+            find_bombs_time.as_secs_f64() / threaded_time.as_secs_f64()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Lets a solver run be stopped from another thread, or once a deadline passes.
+///
+/// Clones share the same flag, so cancelling any of them stops every search using the token.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    /// Also counts as cancelled once `timeout` has passed from now. A timeout too long to have a
+    /// deadline never runs out.
+    pub fn with_timeout(self, timeout: Option<Duration>) -> Self {
+        CancelToken {
+            deadline: timeout.and_then(|timeout| Instant::now().checked_add(timeout)),
+            ..self
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+/// How far along a search is, in first-bomb candidates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub tried: usize,
+    pub total: usize,
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} first bomb candidates tried ({}%)",
+            self.tried,
+            self.total,
+            (self.tried * 100).checked_div(self.total).unwrap_or(100)
+        )
+    }
+}
+
+/// Callback the solvers report their progress to, from whichever worker thread made it.
+pub type ProgressFn<'a> = &'a (dyn Fn(Progress) + Sync);
+
+/// A progress callback for callers that don't need one.
+pub fn no_progress(_: Progress) {}
@@ -1,12 +1,29 @@
+use std::cmp::Ordering;
 use std::fmt;
 use std::ops::{Add, Sub};
 
-#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+/// A cell on the board, by column `x` and row `y`.
+///
+/// Coords order row-major: by row, then by column within the row. Solvers use this order
+/// wherever they list cells, so their results don't depend on hash order.
+#[derive(Debug, Default, Clone, Copy, Hash, Eq, PartialEq)]
 pub struct Coord {
     pub x: usize,
     pub y: usize,
 }
 
+impl Ord for Coord {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.y, self.x).cmp(&(other.y, other.x))
+    }
+}
+
+impl PartialOrd for Coord {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl From<(usize, usize)> for Coord {
     fn from(point: (usize, usize)) -> Self {
         Coord {
@@ -16,12 +33,6 @@ impl From<(usize, usize)> for Coord {
     }
 }
 
-impl Default for Coord {
-    fn default() -> Self {
-        Self { x: 0, y: 0 }
-    }
-}
-
 impl fmt::Display for Coord {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{},{}", self.x, self.y)
@@ -0,0 +1,142 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::bitboard::{BitBoard, TargetBits};
+use crate::coord::Coord;
+use crate::geometry::Geometry;
+
+/// The bombs that destroy as many targets as possible between them, for boards they can't clear.
+#[derive(Debug, Clone, Default)]
+pub struct Coverage {
+    pub bombs: Vec<Coord>,
+    /// Targets none of the bombs reach, in row-major order
+    pub missed: Vec<Coord>,
+    pub target_count: usize,
+}
+
+impl Coverage {
+    pub fn destroyed(&self) -> usize {
+        self.target_count - self.missed.len()
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bombs: Vec<String> = self.bombs.iter().map(|bomb| bomb.to_string()).collect();
+        write!(
+            f,
+            "Bombs at {} destroy {} of {} targets",
+            bombs.join(" "),
+            self.destroyed(),
+            self.target_count
+        )?;
+        if self.missed.is_empty() {
+            write!(f, ".")
+        } else {
+            let missed: Vec<String> = self.missed.iter().map(|target| target.to_string()).collect();
+            write!(f, ", leaving {}.", missed.join(" "))
+        }
+    }
+}
+
+/// Branch and bound search over the cells worth bombing, best first.
+struct Search<'a> {
+    cells: Vec<Coord>,
+    masks: Vec<&'a TargetBits>,
+    target_count: usize,
+    best: Vec<usize>,
+    best_count: usize,
+}
+
+impl<'a> Search<'a> {
+    fn new(bitboard: &'a BitBoard, geometry: &Geometry) -> Self {
+        let mut seen: HashSet<&TargetBits> = HashSet::new();
+        let mut candidates: Vec<(Coord, &TargetBits)> = Vec::new();
+        for cell in geometry.cells() {
+            let mask = bitboard.hit_mask(&cell);
+            // Cells hitting the same targets as one already seen are interchangeable with it
+            if !mask.is_empty() && seen.insert(mask) {
+                candidates.push((cell, mask));
+            }
+        }
+        candidates.sort_by_key(|(_, mask)| std::cmp::Reverse(mask.len()));
+        // A cell hitting only some of the targets another cell hits is never the better choice.
+        // Any bigger mask comes earlier, and one that isn't kept is itself inside one that is, so
+        // only the kept ones hitting the least hit of the mask's targets need checking
+        let mut dominant: Vec<(Coord, &TargetBits)> = Vec::new();
+        // Indices into `dominant` of the masks hitting each target
+        let mut hitting: Vec<Vec<usize>> = vec![Vec::new(); bitboard.target_count()];
+        for (cell, mask) in candidates {
+            let others = mask
+                .indices()
+                .map(|target| &hitting[target])
+                .min_by_key(|others| others.len())
+                .expect("empty masks were skipped");
+            if !others
+                .iter()
+                .any(|other| mask.is_subset(dominant[*other].1))
+            {
+                for target in mask.indices() {
+                    hitting[target].push(dominant.len());
+                }
+                dominant.push((cell, mask));
+            }
+        }
+        Search {
+            cells: dominant.iter().map(|(cell, _)| *cell).collect(),
+            masks: dominant.iter().map(|(_, mask)| *mask).collect(),
+            target_count: bitboard.target_count(),
+            best: Vec::new(),
+            best_count: 0,
+        }
+    }
+
+    fn search(&mut self, start: usize, hit: &TargetBits, chosen: &mut Vec<usize>, bombs_left: usize) {
+        let count = hit.len();
+        if count > self.best_count {
+            self.best_count = count;
+            self.best = chosen.clone();
+        }
+        if bombs_left == 0 || self.best_count == self.target_count {
+            return;
+        }
+        for next in start..self.masks.len() {
+            // Candidates are sorted by how many targets they hit, so the next few bound how many
+            // more the remaining bombs can add, and the bound only shrinks further along
+            let bound = count
+                + self.masks[next..]
+                    .iter()
+                    .take(bombs_left)
+                    .map(|mask| mask.len())
+                    .sum::<usize>();
+            if bound <= self.best_count {
+                break;
+            }
+            chosen.push(next);
+            self.search(next + 1, &(hit | self.masks[next]), chosen, bombs_left - 1);
+            chosen.pop();
+        }
+    }
+}
+
+/// Finds up to `bomb_count` bombs that destroy as many of `targets` as possible. Fewer bombs are
+/// returned when they already destroy every target they could.
+pub fn find_max_coverage(targets: &HashSet<Coord>, geometry: &Geometry, bomb_count: usize) -> Coverage {
+    let bitboard = BitBoard::new(targets, geometry);
+    let mut search = Search::new(&bitboard, geometry);
+    search.search(0, &TargetBits::new(targets.len()), &mut Vec::new(), bomb_count);
+
+    let mut hit = TargetBits::new(targets.len());
+    for index in &search.best {
+        hit = &hit | search.masks[*index];
+    }
+    let mut bombs: Vec<Coord> = search.best.iter().map(|index| search.cells[*index]).collect();
+    bombs.sort_by_key(|bomb| (bomb.y, bomb.x));
+    let mut missed = bitboard.missed(&hit);
+    missed.sort_by_key(|target| (target.y, target.x));
+    Coverage {
+        bombs,
+        missed,
+        target_count: targets.len(),
+    }
+}
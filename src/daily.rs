@@ -0,0 +1,284 @@
+use std::collections::HashSet;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::{Datelike, Local, NaiveDate};
+
+use crate::coord::Coord;
+use crate::errors::TBError;
+use crate::generate::generate_unique;
+use crate::geometry::Geometry;
+
+/// Width and height of every daily puzzle.
+pub const DAILY_SIZE: usize = 12;
+
+// How many targets the daily puzzle starts out with before adding more to make it unique
+const DAILY_TARGETS: usize = 10;
+
+const STATS_FILE_NAME: &str = ".twobombs-daily";
+
+/// Today's date in the local time zone.
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// The seed for `date`'s puzzle: the date written as a YYYYMMDD number.
+pub fn seed(date: NaiveDate) -> u64 {
+    date.year() as u64 * 10_000 + date.month() as u64 * 100 + date.day() as u64
+}
+
+/// The rules the daily puzzle is played by, which are always the classic ones whatever the
+/// command line asks for, so that everyone gets the same board.
+pub fn geometry() -> Geometry {
+    Geometry::queen(DAILY_SIZE, DAILY_SIZE)
+}
+
+/// The targets of `date`'s puzzle, with exactly one two-bomb solution.
+pub fn puzzle(date: NaiveDate) -> Result<HashSet<Coord>, TBError> {
+    generate_unique(&geometry(), DAILY_TARGETS, seed(date))
+}
+
+/// Where the stats are kept when no file was asked for: in the home directory, or the current one
+/// if there isn't one.
+pub fn default_stats_path() -> PathBuf {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join(STATS_FILE_NAME)
+}
+
+/// Formats `duration` as minutes and seconds.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}m{:02}s", seconds / 60, seconds % 60)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Solved,
+    /// The answer was revealed before the player found it
+    GaveUp,
+    /// The player stopped without finishing
+    Unfinished,
+}
+
+impl Outcome {
+    fn name(self) -> &'static str {
+        match self {
+            Outcome::Solved => "solved",
+            Outcome::GaveUp => "gave-up",
+            Outcome::Unfinished => "unfinished",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [Outcome::Solved, Outcome::GaveUp, Outcome::Unfinished]
+            .iter()
+            .copied()
+            .find(|outcome| outcome.name() == name)
+    }
+}
+
+/// How one day's puzzle went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Record {
+    pub date: NaiveDate,
+    pub outcome: Outcome,
+    pub elapsed: Duration,
+    pub hints: usize,
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.date,
+            self.outcome.name(),
+            self.elapsed.as_secs(),
+            self.hints
+        )
+    }
+}
+
+impl Record {
+    /// Parses a stats file line, as written by `Display`.
+    fn parse(line: &str) -> Result<Self, TBError> {
+        let bad_line = || TBError::BadStats(format!("can't read '{}'", line));
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [date, outcome, seconds, hints] => Ok(Record {
+                date: date.parse().map_err(|_| bad_line())?,
+                outcome: Outcome::from_name(outcome).ok_or_else(bad_line)?,
+                elapsed: Duration::from_secs(seconds.parse()?),
+                hints: hints.parse()?,
+            }),
+            _ => Err(bad_line()),
+        }
+    }
+}
+
+/// Every day's result, kept in a text file with one line per day.
+#[derive(Debug, Clone)]
+pub struct Stats {
+    path: PathBuf,
+    records: Vec<Record>,
+}
+
+impl Stats {
+    /// Reads the stats from `path`, starting afresh if there's no file yet.
+    pub fn load(path: &Path) -> Result<Self, TBError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(TBError::Io(e)),
+        };
+        let records = text
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(Record::parse)
+            .collect::<Result<_, _>>()?;
+        Ok(Stats {
+            path: path.to_path_buf(),
+            records,
+        })
+    }
+
+    pub fn save(&self) -> Result<(), TBError> {
+        let mut text = String::from("# date outcome seconds hints\n");
+        for record in &self.records {
+            text.push_str(&format!("{}\n", record));
+        }
+        fs::write(&self.path, text).map_err(TBError::Io)
+    }
+
+    pub fn get(&self, date: NaiveDate) -> Option<&Record> {
+        self.records.iter().find(|record| record.date == date)
+    }
+
+    /// Keeps `record` as its day's result, unless the day was already solved or given up on.
+    pub fn record(&mut self, record: Record) {
+        match self.records.iter_mut().find(|old| old.date == record.date) {
+            Some(old) if old.outcome == Outcome::Unfinished => *old = record,
+            Some(_) => (),
+            None => {
+                self.records.push(record);
+                self.records.sort_by_key(|record| record.date);
+            }
+        }
+    }
+
+    /// Number of days in a row with a solved puzzle, up to `date`, or the day before if `date`'s
+    /// puzzle isn't solved yet.
+    pub fn streak(&self, date: NaiveDate) -> usize {
+        let mut streak = 0;
+        let mut day = match self.get(date) {
+            Some(record) if record.outcome == Outcome::Solved => Some(date),
+            _ => date.pred_opt(),
+        };
+        while let Some(date) = day {
+            match self.get(date) {
+                Some(record) if record.outcome == Outcome::Solved => streak += 1,
+                _ => break,
+            }
+            day = date.pred_opt();
+        }
+        streak
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let solved: Vec<&Record> = self
+            .records
+            .iter()
+            .filter(|record| record.outcome == Outcome::Solved)
+            .collect();
+        write!(
+            f,
+            "{} daily puzzles played, {} solved",
+            self.records.len(),
+            solved.len()
+        )?;
+        if !solved.is_empty() {
+            let total: Duration = solved.iter().map(|record| record.elapsed).sum();
+            write!(
+                f,
+                " in {} on average",
+                format_duration(total / solved.len() as u32)
+            )?;
+        }
+        let streak = self.streak(today());
+        write!(
+            f,
+            ", current streak {} day{}",
+            streak,
+            if streak == 1 { "" } else { "s" }
+        )
+    }
+}
+
+/// A daily puzzle being played in the terminal.
+#[derive(Debug)]
+pub struct DailyGame {
+    pub date: NaiveDate,
+    pub started: Instant,
+    /// Time and hints spent on the puzzle in earlier sessions that stopped without finishing it
+    earlier: Option<Record>,
+    pub stats: Stats,
+    /// Where the player has put bombs so far
+    pub guesses: Vec<Coord>,
+    /// Whether the puzzle has been solved or given up on, after which nothing more is recorded
+    pub finished: bool,
+}
+
+impl DailyGame {
+    pub fn new(date: NaiveDate, stats: Stats) -> Self {
+        // A day that's already done can be played again, but doesn't count twice
+        let finished = stats
+            .get(date)
+            .is_some_and(|record| record.outcome != Outcome::Unfinished);
+        // A day that was left unfinished carries on where it stopped
+        let earlier = stats
+            .get(date)
+            .filter(|record| record.outcome == Outcome::Unfinished)
+            .copied();
+        DailyGame {
+            date,
+            started: Instant::now(),
+            earlier,
+            stats,
+            guesses: Vec::new(),
+            finished,
+        }
+    }
+
+    /// Time spent on the puzzle so far, counting earlier unfinished sessions.
+    pub fn elapsed(&self) -> Duration {
+        self.earlier
+            .map_or(Duration::default(), |record| record.elapsed)
+            + self.started.elapsed()
+    }
+
+    /// Records how the puzzle went and saves the stats, adding `hints` from this session to any
+    /// from earlier ones. Only the first finish counts.
+    pub fn finish(&mut self, outcome: Outcome, hints: usize) -> Result<(), TBError> {
+        if self.finished {
+            return Ok(());
+        }
+        self.finished = outcome != Outcome::Unfinished;
+        self.stats.record(Record {
+            date: self.date,
+            outcome,
+            elapsed: self.elapsed(),
+            hints: self.earlier.map_or(0, |record| record.hints) + hints,
+        });
+        self.stats.save()
+    }
+}
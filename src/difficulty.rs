@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::bitboard::BitBoard;
+use crate::control::{no_progress, CancelToken};
+use crate::coord::Coord;
+use crate::geometry::Geometry;
+use crate::parallel;
+
+// Scores below these are rated easy and medium, anything else hard
+const EASY_BELOW: u32 = 30;
+const MEDIUM_BELOW: u32 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Medium => write!(f, "medium"),
+            Difficulty::Hard => write!(f, "hard"),
+        }
+    }
+}
+
+/// How hard a solvable board is, along with what went into the score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rating {
+    pub score: u32,
+    pub difficulty: Difficulty,
+    pub target_count: usize,
+    pub solutions: usize,
+    /// First bombs still worth trying once symmetry and hit counts have ruled out the rest
+    pub first_bombs: usize,
+    /// Targets every solution hits along the same line, so a player can work out that a bomb
+    /// goes somewhere on it
+    pub forced: usize,
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Difficulty: {} ({})", self.difficulty, self.score)
+    }
+}
+
+impl Rating {
+    /// The numbers behind the score, one per line.
+    pub fn breakdown(&self) -> String {
+        format!(
+            "{} targets\n{} solutions\n{} first bombs left after pruning\n{} targets with a forced line",
+            self.target_count, self.solutions, self.first_bombs, self.forced
+        )
+    }
+}
+
+/// Number of first bombs that survive pruning: one per symmetry orbit, and only those hitting
+/// enough targets that the best second bomb could make up the rest.
+fn surviving_first_bombs(bitboard: &BitBoard, geometry: &Geometry) -> usize {
+    let best = geometry
+        .cells()
+        .map(|cell| bitboard.hit_mask(&cell).len())
+        .max()
+        .unwrap_or(0);
+    bitboard
+        .first_bomb_representatives()
+        .iter()
+        .filter(|b1| bitboard.hit_mask(b1).len() + best >= bitboard.target_count())
+        .count()
+}
+
+/// Number of targets that every one of `solutions` hits along the same line through the target,
+/// or always with a bomb on the target itself.
+fn forced_lines(
+    targets: &HashSet<Coord>,
+    geometry: &Geometry,
+    solutions: &[(Coord, Coord)],
+) -> usize {
+    targets
+        .iter()
+        .filter(|target| {
+            let mut lines = HashSet::new();
+            for (b1, b2) in solutions {
+                for bomb in [b1, b2] {
+                    if geometry.hits(bomb, target) {
+                        // A ray and its opposite make up the same line
+                        let line = geometry
+                            .hit_ray(bomb, target)
+                            .map(|(dx, dy)| (dx, dy).max((-dx, -dy)));
+                        lines.insert(line);
+                    }
+                }
+                if lines.len() > 1 {
+                    return false;
+                }
+            }
+            lines.len() == 1
+        })
+        .count()
+}
+
+/// Rates how hard it is to find two bombs clearing `targets`, or `None` if there aren't any.
+///
+/// The score grows with the number of first bombs a player has to weigh up and with the number of
+/// targets to keep track of, and shrinks with every extra solution to stumble upon and every
+/// forced deduction handing the player a place to start. Each term is logarithmic, so no single
+/// one swamps the others.
+pub fn rate(targets: &HashSet<Coord>, geometry: &Geometry, threads: usize) -> Option<Rating> {
+    let bitboard = BitBoard::new(targets, geometry);
+    let solutions =
+        parallel::find_all_bombs(&bitboard, threads, &CancelToken::new(), &no_progress).solutions;
+    rate_solved(targets, geometry, &solutions)
+}
+
+/// Rates `targets` the same way as [`rate`], for callers that have already found all of their
+/// `solutions` and don't want them searched for again.
+pub fn rate_solved(
+    targets: &HashSet<Coord>,
+    geometry: &Geometry,
+    solutions: &[(Coord, Coord)],
+) -> Option<Rating> {
+    if targets.is_empty() || solutions.is_empty() {
+        return None;
+    }
+    let bitboard = BitBoard::new(targets, geometry);
+    let first_bombs = surviving_first_bombs(&bitboard, geometry);
+    let forced = forced_lines(targets, geometry, solutions);
+
+    let log = |count: usize| (count as f64 + 1.0).log2();
+    let score = 10.0 * log(first_bombs) + 8.0 * log(targets.len())
+        - 10.0 * log(solutions.len() - 1)
+        - 5.0 * log(forced);
+    let score = score.max(0.0).round() as u32;
+    let difficulty = if score < EASY_BELOW {
+        Difficulty::Easy
+    } else if score < MEDIUM_BELOW {
+        Difficulty::Medium
+    } else {
+        Difficulty::Hard
+    };
+    Some(Rating {
+        score,
+        difficulty,
+        target_count: targets.len(),
+        solutions: solutions.len(),
+        first_bombs,
+        forced,
+    })
+}
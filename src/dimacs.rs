@@ -0,0 +1,236 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::coord::Coord;
+use crate::errors::TBError;
+use crate::geometry::Geometry;
+use crate::twobombs::test_explode;
+
+/// A board's targets as a SAT instance: is there a placement of at most `bomb_count` bombs that
+/// destroys every target?
+///
+/// Variables `1..=cells.len()` stand for a bomb on the matching cell, which are the cells that hit
+/// at least one target, in row-major order. Each target gets a clause over the cells that hit it,
+/// and the limit on bombs is a sequential counter over the cell variables, which takes the
+/// remaining variables.
+#[derive(Debug, Clone)]
+pub struct Cnf {
+    pub cells: Vec<Coord>,
+    pub bomb_count: usize,
+    pub variables: usize,
+    pub clauses: Vec<Vec<isize>>,
+}
+
+impl Cnf {
+    pub fn new(targets: &HashSet<Coord>, geometry: &Geometry, bomb_count: usize) -> Self {
+        let mut cells: Vec<Coord> = geometry.attack_points(targets).into_iter().collect();
+        cells.sort_by_key(|cell| (cell.y, cell.x));
+        let mut cnf = Cnf {
+            variables: cells.len(),
+            cells,
+            bomb_count,
+            clauses: Vec::new(),
+        };
+
+        let mut targets: Vec<&Coord> = targets.iter().collect();
+        targets.sort_by_key(|target| (target.y, target.x));
+        for target in targets {
+            let clause = (0..cnf.cells.len())
+                .filter(|index| geometry.hits(&cnf.cells[*index], target))
+                .map(|index| index as isize + 1)
+                .collect();
+            cnf.clauses.push(clause);
+        }
+        cnf.add_at_most(bomb_count);
+        cnf
+    }
+
+    /// Adds Sinz's sequential counter encoding of "at most `k` of the cell variables are true".
+    fn add_at_most(&mut self, k: usize) {
+        let n = self.cells.len();
+        if k == 0 {
+            for cell in 1..=n {
+                self.clauses.push(vec![-(cell as isize)]);
+            }
+            return;
+        }
+        if n <= k {
+            return;
+        }
+        let x = |i: usize| i as isize + 1;
+        // s(i, j) is true when at least j + 1 of the first i + 1 cells have a bomb
+        let first = self.variables as isize + 1;
+        let s = |i: usize, j: usize| first + (i * k + j) as isize;
+        self.variables += (n - 1) * k;
+
+        self.clauses.push(vec![-x(0), s(0, 0)]);
+        for j in 1..k {
+            self.clauses.push(vec![-s(0, j)]);
+        }
+        for i in 1..n - 1 {
+            self.clauses.push(vec![-x(i), s(i, 0)]);
+            self.clauses.push(vec![-s(i - 1, 0), s(i, 0)]);
+            for j in 1..k {
+                self.clauses.push(vec![-x(i), -s(i - 1, j - 1), s(i, j)]);
+                self.clauses.push(vec![-s(i - 1, j), s(i, j)]);
+            }
+            self.clauses.push(vec![-x(i), -s(i - 1, k - 1)]);
+        }
+        self.clauses.push(vec![-x(n - 1), -s(n - 2, k - 1)]);
+    }
+
+    /// Reads a SAT solver's output for this instance back into a bomb placement, checking that
+    /// the bombs really do clear `targets`. Returns `None` if the solver found no model.
+    ///
+    /// Both the competition output format, with the model on `v` lines, and a bare list of
+    /// literals are accepted.
+    pub fn read_model(
+        &self,
+        model: &str,
+        targets: &HashSet<Coord>,
+        geometry: &Geometry,
+    ) -> Result<Option<Vec<Coord>>, TBError> {
+        let mut bombs = Vec::new();
+        let mut literal_count = 0;
+        for line in model.lines().map(|line| line.trim()) {
+            if line.starts_with('c') || line.is_empty() {
+                continue;
+            }
+            if line.contains("UNSAT") {
+                return Ok(None);
+            }
+            if line.starts_with('s') || line == "SAT" {
+                continue;
+            }
+            let literals = line.strip_prefix('v').unwrap_or(line).split_whitespace();
+            for literal in literals {
+                let literal: isize = literal
+                    .parse()
+                    .map_err(|_| TBError::BadModel(format!("'{}' isn't a literal", literal)))?;
+                literal_count += 1;
+                if literal > 0 && literal as usize <= self.cells.len() {
+                    bombs.push(self.cells[literal as usize - 1]);
+                }
+            }
+        }
+
+        if literal_count == 0 {
+            return Err(TBError::BadModel(String::from("no model found")));
+        }
+        if bombs.len() > self.bomb_count {
+            return Err(TBError::BadModel(format!(
+                "{} bombs placed, at most {} allowed",
+                bombs.len(),
+                self.bomb_count
+            )));
+        }
+        let standing = bombs.iter().fold(targets.clone(), |remaining, bomb| {
+            test_explode(bomb, &remaining, geometry)
+        });
+        if !standing.is_empty() {
+            let mut standing: Vec<Coord> = standing.into_iter().collect();
+            standing.sort_by_key(|target| (target.y, target.x));
+            let standing: Vec<String> = standing.iter().map(|target| target.to_string()).collect();
+            return Err(TBError::BadModel(format!(
+                "targets at {} are left standing",
+                standing.join(" ")
+            )));
+        }
+        Ok(Some(bombs))
+    }
+}
+
+impl fmt::Display for Cnf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "c twobombs: at most {} bombs", self.bomb_count)?;
+        for (index, cell) in self.cells.iter().enumerate() {
+            writeln!(f, "c variable {} is a bomb at {}", index + 1, cell)?;
+        }
+        writeln!(f, "p cnf {} {}", self.variables, self.clauses.len())?;
+        for clause in &self.clauses {
+            for literal in clause {
+                write!(f, "{} ", literal)?;
+            }
+            writeln!(f, "0")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Whether some setting of the counter's variables satisfies every clause once the cell
+    /// variables are set to `cells`, by trying them all.
+    fn satisfiable(cnf: &Cnf, cells: &[bool]) -> bool {
+        let counters = cnf.variables - cells.len();
+        (0..1u64 << counters).any(|counter_bits| {
+            let value = |variable: usize| match variable.checked_sub(cells.len() + 1) {
+                Some(counter) => counter_bits & (1 << counter) != 0,
+                None => cells[variable - 1],
+            };
+            cnf.clauses.iter().all(|clause| {
+                clause
+                    .iter()
+                    .any(|literal| value(literal.unsigned_abs()) == (*literal > 0))
+            })
+        })
+    }
+
+    #[test]
+    fn at_most_k_allows_exactly_up_to_k_bombs() {
+        for n in 1..=5 {
+            for k in 0..=3 {
+                let mut cnf = Cnf {
+                    cells: (0..n).map(|x| Coord { x, y: 0 }).collect(),
+                    bomb_count: k,
+                    variables: n,
+                    clauses: Vec::new(),
+                };
+                cnf.add_at_most(k);
+                for bits in 0..1u32 << n {
+                    let cells: Vec<bool> = (0..n).map(|i| bits & (1 << i) != 0).collect();
+                    assert_eq!(
+                        satisfiable(&cnf, &cells),
+                        bits.count_ones() as usize <= k,
+                        "n = {}, k = {}, bombs {:0width$b}",
+                        n,
+                        k,
+                        bits,
+                        width = n
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn read_model_checks_the_bombs() {
+        let geometry = Geometry::queen(4, 4);
+        let (a, b) = (Coord { x: 0, y: 0 }, Coord { x: 1, y: 2 });
+        let targets: HashSet<Coord> = [a, b].iter().copied().collect();
+        let cnf = Cnf::new(&targets, &geometry, 1);
+        let variable = |cell: &Coord| cnf.cells.iter().position(|other| other == cell).unwrap() + 1;
+
+        // A bomb on `a` doesn't reach `b`, a knight's move away
+        let model = format!("s SATISFIABLE\nv {} 0\n", variable(&a));
+        assert!(matches!(
+            cnf.read_model(&model, &targets, &geometry),
+            Err(TBError::BadModel(_))
+        ));
+
+        // A bomb on the same row as one and the same column as the other clears both
+        let both = Coord { x: 0, y: 2 };
+        let model = format!("s SATISFIABLE\nv {} 0\n", variable(&both));
+        assert_eq!(
+            cnf.read_model(&model, &targets, &geometry).unwrap(),
+            Some(vec![both])
+        );
+        assert_eq!(
+            cnf.read_model("s UNSATISFIABLE\n", &targets, &geometry)
+                .unwrap(),
+            None
+        );
+    }
+}
@@ -1,21 +1,45 @@
+use std::io;
 use std::num;
 
 use thiserror::Error;
 
 use crate::coord::Coord;
 
+/// Everything that can go wrong in the library and the game.
+///
+/// The logger and terminal errors only exist with the `tui` feature, so the enum is non-exhaustive
+/// to keep matches on it the same whichever features are on.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum TBError {
     #[error("Invalid game input")]
     InvalidGameInput,
     #[error("Invalid cell position: {0}")]
     InvalidCellPosition(Coord),
+    #[error("Invalid board: {0}")]
+    BadBoard(String),
+    #[error("Invalid SAT model: {0}")]
+    BadModel(String),
+    #[error("Can't generate puzzle: {0}")]
+    BadPuzzle(String),
+    #[error("Invalid stats file: {0}")]
+    BadStats(String),
+    #[error("Search timed out")]
+    TimedOut,
+    #[error("Solver thread stopped unexpectedly")]
+    SolverFailed,
+    #[error("Unknown attack pattern: {0}")]
+    UnknownPattern(String),
+    #[cfg(feature = "tui")]
     #[error("Logger error: {0}")]
     Logger(#[from] flexi_logger::FlexiLoggerError),
+    #[cfg(feature = "tui")]
     #[error("Terminal error: {0}")]
     Terminal(#[from] crossterm::ErrorKind),
     #[error("Parse error: {0}")]
     BadParse(#[from] num::ParseIntError),
+    #[error("I/O error: {0}")]
+    Io(io::Error),
 }
 
 pub type Result<T> = std::result::Result<T, TBError>;
@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+
+use crate::bitboard::BitBoard;
+use crate::control::CancelToken;
+use crate::coord::Coord;
+use crate::errors::TBError;
+use crate::geometry::Geometry;
+use crate::rng::Rng;
+use crate::twobombs::find_n_bombs;
+
+// How many boards to try before giving up on finding one that fits the spec
+const MAX_ATTEMPTS: usize = 1000;
+
+// How many times the starting number of targets `generate_unique` goes up to
+const MAX_TARGET_GROWTH: usize = 4;
+
+/// What kind of puzzle to generate.
+#[derive(Debug, Clone, Copy)]
+pub struct PuzzleSpec {
+    pub target_count: usize,
+    /// How many bombs it takes to clear the targets, with fewer never being enough
+    pub bomb_count: usize,
+    /// Only accept boards exactly one pair of bombs clears, which needs `bomb_count` to be 2
+    pub unique: bool,
+}
+
+/// Picks `count` distinct cells from `cells`.
+fn choose(rng: &mut Rng, cells: &[Coord], count: usize) -> Vec<Coord> {
+    let mut cells = cells.to_vec();
+    for i in 0..count {
+        let j = i + rng.below(cells.len() - i);
+        cells.swap(i, j);
+    }
+    cells.truncate(count);
+    cells
+}
+
+fn fits(targets: &HashSet<Coord>, geometry: &Geometry, spec: &PuzzleSpec) -> bool {
+    let cancel = CancelToken::new();
+    if find_n_bombs(targets, geometry, spec.bomb_count - 1, &cancel).is_some() {
+        return false;
+    }
+    if !spec.unique {
+        return true;
+    }
+    // Listing solutions stops at the second, which is enough to know there's more than one
+    let board = &BitBoard::new(targets, geometry);
+    let solutions = board.first_bombs().into_iter().flat_map(|b1| {
+        board
+            .second_bombs(&b1)
+            .into_iter()
+            .filter(move |b2| !board.is_duplicate(&b1, b2))
+    });
+    solutions.take(2).count() == 1
+}
+
+/// Generates a set of targets matching `spec`, always the same one for the same geometry, spec
+/// and seed.
+///
+/// Bombs are planted on random cells first and the targets picked from the cells they blast, so
+/// the planted bombs always clear the board. Boards fewer bombs could clear, or with more than
+/// one solution when `spec.unique` is set, are thrown away and another one tried.
+pub fn generate(
+    geometry: &Geometry,
+    spec: &PuzzleSpec,
+    seed: u64,
+) -> Result<HashSet<Coord>, TBError> {
+    if spec.target_count == 0 || spec.bomb_count == 0 {
+        return Err(TBError::BadPuzzle(String::from(
+            "it needs at least one target and one bomb",
+        )));
+    }
+    if spec.unique && spec.bomb_count != 2 {
+        return Err(TBError::BadPuzzle(String::from(
+            "a unique solution can only be asked for with two bombs",
+        )));
+    }
+    let cells: Vec<Coord> = geometry.cells().collect();
+    if cells.len() < spec.bomb_count {
+        return Err(TBError::BadPuzzle(format!(
+            "the board only has room for {} bombs",
+            cells.len()
+        )));
+    }
+
+    let mut rng = Rng::new(seed);
+    for _ in 0..MAX_ATTEMPTS {
+        let bombs = choose(&mut rng, &cells, spec.bomb_count);
+        let blasted: HashSet<Coord> = bombs.iter().flat_map(|bomb| geometry.blast(bomb)).collect();
+        if blasted.len() < spec.target_count {
+            continue;
+        }
+        // Sorted, so the same seed picks the same targets whatever the hash order
+        let mut blasted: Vec<Coord> = blasted.into_iter().collect();
+        blasted.sort();
+        let targets = choose(&mut rng, &blasted, spec.target_count)
+            .into_iter()
+            .collect();
+        if fits(&targets, geometry, spec) {
+            return Ok(targets);
+        }
+    }
+    Err(TBError::BadPuzzle(format!(
+        "no board fit after {} tries, try a different number of targets",
+        MAX_ATTEMPTS
+    )))
+}
+
+/// Generates a puzzle with exactly one two-bomb solution, starting out with `target_count`
+/// targets and adding more until there are enough to rule out every other pair. Small boards
+/// need more targets than big ones.
+pub fn generate_unique(
+    geometry: &Geometry,
+    target_count: usize,
+    seed: u64,
+) -> Result<HashSet<Coord>, TBError> {
+    let mut spec = PuzzleSpec {
+        target_count,
+        bomb_count: 2,
+        unique: true,
+    };
+    loop {
+        match generate(geometry, &spec, seed) {
+            Err(TBError::BadPuzzle(_)) if spec.target_count < target_count * MAX_TARGET_GROWTH => {
+                spec.target_count += 2
+            }
+            result => return result,
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::attack::AttackPattern;
+use crate::control::CancelToken;
+use crate::coord::Coord;
+use crate::errors::TBError;
+
+/// A cell on a board that may be unbounded, by signed column `x` and row `y`.
+///
+/// Points order row-major, the same as [`Coord`].
+#[derive(Debug, Default, Clone, Copy, Hash, Eq, PartialEq)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Ord for Point {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.y, self.x).cmp(&(other.y, other.x))
+    }
+}
+
+impl PartialOrd for Point {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<Coord> for Point {
+    fn from(coord: Coord) -> Self {
+        Point {
+            x: coord.x as i64,
+            y: coord.y as i64,
+        }
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl FromStr for Point {
+    type Err = TBError;
+
+    /// Parses a point written as `x,y`, the way `Display` writes it.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(',') {
+            Some((x, y)) => Ok(Point {
+                x: x.trim().parse()?,
+                y: y.trim().parse()?,
+            }),
+            None => Err(TBError::BadBoard(format!("can't read point '{}'", s))),
+        }
+    }
+}
+
+/// Reads a list of targets with one `x,y` point per line, skipping blank lines and lines starting
+/// with '#'.
+pub fn parse_points(text: &str) -> Result<HashSet<Point>, TBError> {
+    text.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.parse())
+        .collect()
+}
+
+fn cross(a: (i128, i128), b: (i128, i128)) -> i128 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+fn offset(from: &Point, to: &Point) -> (i128, i128) {
+    (to.x as i128 - from.x as i128, to.y as i128 - from.y as i128)
+}
+
+/// A board whose blasts travel in whole lines, solved by working out where the lines through the
+/// targets cross instead of looking at every cell, so the board can be as big as a coordinate
+/// allows or have no edges at all.
+#[derive(Debug, Clone)]
+pub struct LineBoard {
+    /// One direction per opposite pair of the pattern's rays
+    directions: Vec<(i128, i128)>,
+    /// Width and height of the board, or `None` for an unbounded plane
+    bounds: Option<(i64, i64)>,
+}
+
+impl LineBoard {
+    /// Sets up a board played with `pattern`, which has to blast all the way to the edge of the
+    /// board, so for now queen, rook or bishop.
+    pub fn new(pattern: &dyn AttackPattern, bounds: Option<(i64, i64)>) -> Result<Self, TBError> {
+        if pattern.reach().is_some() {
+            return Err(TBError::BadBoard(format!(
+                "the line solver needs blasts that go all the way, not {} blasts",
+                pattern.name()
+            )));
+        }
+        let mut directions: Vec<(i128, i128)> = Vec::new();
+        for (dx, dy) in pattern.rays() {
+            let (dx, dy) = (*dx as i128, *dy as i128);
+            if !directions.contains(&(-dx, -dy)) {
+                directions.push((dx, dy));
+            }
+        }
+        Ok(LineBoard { directions, bounds })
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        match self.bounds {
+            Some((width, height)) => {
+                (0..width).contains(&point.x) && (0..height).contains(&point.y)
+            }
+            None => true,
+        }
+    }
+
+    /// Whether a bomb at `bomb` destroys a target at `target`.
+    pub fn hits(&self, bomb: &Point, target: &Point) -> bool {
+        let step = offset(bomb, target);
+        bomb == target
+            || self
+                .directions
+                .iter()
+                .any(|direction| cross(step, *direction) == 0)
+    }
+
+    /// Where the line through `a` along `da` crosses the line through `b` along `db`, if they
+    /// cross on a cell of the board.
+    fn intersection(
+        &self,
+        a: &Point,
+        da: (i128, i128),
+        b: &Point,
+        db: (i128, i128),
+    ) -> Option<Point> {
+        let det = cross(da, db);
+        if det == 0 {
+            return None;
+        }
+        // Solve a + s * da = b + t * db for s. Directions are whole steps with no common factor,
+        // so the crossing is on a cell exactly when s is a whole number
+        let numerator = cross(offset(a, b), db);
+        if numerator % det != 0 {
+            return None;
+        }
+        let steps = numerator / det;
+        let point = Point {
+            x: i64::try_from(a.x as i128 + steps * da.0).ok()?,
+            y: i64::try_from(a.y as i128 + steps * da.1).ok()?,
+        };
+        Some(point).filter(|point| self.contains(point))
+    }
+
+    /// The cells worth trying for a bomb that has to hit `pivot` along with as many of `others`
+    /// as it can: `pivot` itself, and everywhere a line through `pivot` crosses a line through
+    /// one of `others`.
+    ///
+    /// A bomb hitting targets along a single line can just as well go on `pivot`, which lies on
+    /// that line, and a bomb hitting targets along two different lines sits where they cross.
+    fn candidates<'a>(
+        &'a self,
+        pivot: &'a Point,
+        others: &'a [Point],
+    ) -> impl Iterator<Item = Point> + 'a {
+        let crossings = self.directions.iter().flat_map(move |da| {
+            others.iter().flat_map(move |other| {
+                self.directions
+                    .iter()
+                    .filter_map(move |db| self.intersection(pivot, *da, other, *db))
+            })
+        });
+        std::iter::once(*pivot).chain(crossings)
+    }
+
+    /// Somewhere other than `bomb` to put a bomb that isn't needed.
+    fn spare_cell(&self, bomb: &Point, targets: &[Point]) -> Option<Point> {
+        let neighbours = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+            .iter()
+            .filter_map(|(dx, dy)| {
+                Some(Point {
+                    x: bomb.x.checked_add(*dx)?,
+                    y: bomb.y.checked_add(*dy)?,
+                })
+            });
+        targets
+            .iter()
+            .copied()
+            .chain(neighbours)
+            .find(|cell| cell != bomb && self.contains(cell))
+    }
+
+    /// The earliest candidate for a bomb that clears `first` and all of `rest` on its own.
+    fn second_bomb(&self, first: &Point, rest: &[Point]) -> Option<Point> {
+        let clears = |bomb: &Point| {
+            self.hits(bomb, first) && rest.iter().all(|target| self.hits(bomb, target))
+        };
+        match rest.iter().find(|target| !self.hits(first, target)) {
+            // The bomb has to be on a line through each of the two, and since they don't share
+            // one, that leaves only the few cells where those lines cross
+            Some(other) => self
+                .candidates(first, std::slice::from_ref(other))
+                .filter(clears)
+                .min(),
+            None => self.candidates(first, rest).filter(clears).min(),
+        }
+    }
+
+    /// Finds two bombs that clear all of `targets`, or `None` if there aren't any or `cancel` was
+    /// cancelled first.
+    ///
+    /// The first bomb always hits the first target in row-major order, and is the earliest in
+    /// row-major order of the candidates that lead to a solution; the second bomb is the earliest
+    /// candidate that goes with it. Only the cells where lines through the targets cross are
+    /// tried, so the work grows with the number of targets but not with the size of the board.
+    pub fn find_bombs(
+        &self,
+        targets: &HashSet<Point>,
+        cancel: &CancelToken,
+    ) -> Option<(Point, Point)> {
+        let mut targets: Vec<Point> = targets.iter().copied().collect();
+        targets.sort();
+        let (pivot, others) = targets.split_first()?;
+        let mut first_bombs: Vec<Point> = self.candidates(pivot, others).collect();
+        first_bombs.sort();
+        first_bombs.dedup();
+        log::debug!("{} first bomb candidates", first_bombs.len());
+
+        for b1 in first_bombs {
+            if cancel.is_cancelled() {
+                return None;
+            }
+            let missed: Vec<Point> = targets
+                .iter()
+                .filter(|target| !self.hits(&b1, target))
+                .copied()
+                .collect();
+            let b2 = match missed.split_first() {
+                // The first bomb cleared everything by itself, so the second may go anywhere
+                None => self.spare_cell(&b1, &targets),
+                Some((first, rest)) => self.second_bomb(first, rest),
+            };
+            if let Some(b2) = b2 {
+                return Some((b1, b2));
+            }
+        }
+        None
+    }
+}
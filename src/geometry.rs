@@ -0,0 +1,211 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::attack::{AttackPattern, HexLines, Queen};
+use crate::coord::Coord;
+
+/// How the edges of the board behave.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    /// Blasts stop at the edge of the board
+    Plane,
+    /// Blasts leaving one edge come back in at the opposite one
+    Torus,
+}
+
+/// The shape of the cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grid {
+    Square,
+    /// Hexagons in rows, with odd rows shifted half a cell to the right. Cells are still addressed
+    /// by column and row, but rays step in axial coordinates, where the row stays the same and the
+    /// column is counted without the shift.
+    Hex,
+}
+
+/// The board shape and blast rules the solvers work against.
+#[derive(Debug, Clone)]
+pub struct Geometry {
+    pub width: usize,
+    pub height: usize,
+    pub pattern: Arc<dyn AttackPattern>,
+    /// How far blasts travel, as Chebyshev distance from the bomb, or `None` for no limit beyond
+    /// the pattern's own reach
+    pub radius: Option<usize>,
+    /// Cells that stop blasts and can't hold a bomb
+    pub walls: HashSet<Coord>,
+    pub topology: Topology,
+    pub grid: Grid,
+}
+
+impl Geometry {
+    pub fn new(width: usize, height: usize, pattern: Arc<dyn AttackPattern>) -> Self {
+        Geometry {
+            width,
+            height,
+            pattern,
+            radius: None,
+            walls: HashSet::new(),
+            topology: Topology::Plane,
+            grid: Grid::Square,
+        }
+    }
+
+    /// A board of the given size played with the classic queen blasts.
+    pub fn queen(width: usize, height: usize) -> Self {
+        Geometry::new(width, height, Arc::new(Queen))
+    }
+
+    /// A hex board of the given size, with blasts along the three hex axes.
+    pub fn hex(width: usize, height: usize) -> Self {
+        Geometry {
+            grid: Grid::Hex,
+            ..Geometry::new(width, height, Arc::new(HexLines))
+        }
+    }
+
+    pub fn with_radius(self, radius: Option<usize>) -> Self {
+        Geometry { radius, ..self }
+    }
+
+    pub fn with_topology(self, topology: Topology) -> Self {
+        Geometry { topology, ..self }
+    }
+
+    /// How many steps a blast goes along `ray` before stopping, not counting the board edge.
+    fn reach(&self, ray: &(isize, isize)) -> usize {
+        // On a torus a ray only ends when it comes back around to the bomb, which takes at most
+        // one step per cell
+        let board_reach = match self.topology {
+            Topology::Plane => self.width.max(self.height),
+            Topology::Torus => self.width * self.height,
+        };
+        let pattern_reach = self.pattern.reach().unwrap_or(board_reach);
+        match self.radius {
+            Some(radius) => {
+                let step_distance = ray.0.unsigned_abs().max(ray.1.unsigned_abs());
+                pattern_reach.min(radius / step_distance)
+            }
+            None => pattern_reach,
+        }
+    }
+
+    /// Position of `coord` in a row-major array of every cell on the board.
+    pub fn index(&self, coord: &Coord) -> usize {
+        coord.y * self.width + coord.x
+    }
+
+    pub fn contains(&self, coord: &Coord) -> bool {
+        coord.x < self.width && coord.y < self.height
+    }
+
+    /// Every cell that isn't a wall, in row-major order.
+    pub fn cells(&self) -> impl Iterator<Item = Coord> + '_ {
+        let width = self.width;
+        (0..self.height)
+            .flat_map(move |y| (0..width).map(move |x| Coord { x, y }))
+            .filter(move |coord| !self.walls.contains(coord))
+    }
+
+    /// The cell one step along `ray` from `from`, or `None` if that's off the board.
+    fn step(&self, from: &Coord, ray: &(isize, isize)) -> Option<Coord> {
+        let y = from.y as isize + ray.1;
+        let x = match self.grid {
+            Grid::Square => from.x as isize + ray.0,
+            Grid::Hex => {
+                // Undo the odd row shift before stepping and redo it for the row landed on
+                let shift = |row: isize| (row - (row & 1)) / 2;
+                from.x as isize - shift(from.y as isize) + ray.0 + shift(y)
+            }
+        };
+        match self.topology {
+            Topology::Plane if x < 0 || y < 0 => None,
+            Topology::Plane => Some(Coord {
+                x: x as usize,
+                y: y as usize,
+            })
+            .filter(|coord| self.contains(coord)),
+            Topology::Torus => Some(Coord {
+                x: x.rem_euclid(self.width as isize) as usize,
+                y: y.rem_euclid(self.height as isize) as usize,
+            }),
+        }
+    }
+
+    /// The cells a blast from `origin` passes over along `ray`, stopping at the edge of the board,
+    /// short of the first wall, or when it wraps back around to `origin`.
+    fn ray<'a>(&'a self, origin: &Coord, ray: &'a (isize, isize)) -> impl Iterator<Item = Coord> + 'a {
+        let origin = *origin;
+        std::iter::successors(self.step(&origin, ray), move |cell| self.step(cell, ray))
+            .take(self.reach(ray))
+            .take_while(move |cell| *cell != origin && !self.walls.contains(cell))
+    }
+
+    /// Every cell a bomb at `origin` destroys, starting with `origin` itself. Each ray stops short
+    /// of the first wall in its way.
+    pub fn blast(&self, origin: &Coord) -> Vec<Coord> {
+        let mut cells = vec![*origin];
+        for ray in self.pattern.rays() {
+            cells.extend(self.ray(origin, ray));
+        }
+        if self.topology == Topology::Torus {
+            // Rays can wrap around onto each other's cells, so drop the repeats
+            let mut seen = HashSet::new();
+            cells.retain(|cell| seen.insert(*cell));
+        }
+        cells
+    }
+
+    /// Whether a bomb at `bomb` destroys a target at `target`.
+    pub fn hits(&self, bomb: &Coord, target: &Coord) -> bool {
+        if self.walls.contains(bomb) || self.walls.contains(target) {
+            return false;
+        }
+        bomb == target || self.hit_ray(bomb, target).is_some()
+    }
+
+    /// The ray of a bomb at `bomb` whose blast passes over `target`, or `None` if none does. A
+    /// bomb on the target's own cell hits it without any ray, so that's `None` too.
+    pub fn hit_ray(&self, bomb: &Coord, target: &Coord) -> Option<(isize, isize)> {
+        if bomb == target || self.walls.contains(bomb) || self.walls.contains(target) {
+            return None;
+        }
+        if self.grid == Grid::Hex || self.topology == Topology::Torus || !self.walls.is_empty() {
+            // Hex rows, wrapping and walls make the arithmetic below wrong, so follow the rays
+            // instead
+            return self
+                .pattern
+                .rays()
+                .iter()
+                .find(|ray| self.ray(bomb, ray).any(|cell| cell == *target))
+                .copied();
+        }
+        let dx = target.x as isize - bomb.x as isize;
+        let dy = target.y as isize - bomb.y as isize;
+        self.pattern
+            .rays()
+            .iter()
+            .find(|ray| {
+                let (rx, ry) = ray;
+                // How many steps along the ray it takes to cover the x or y distance
+                let steps = if *rx != 0 { dx / rx } else { dy / ry };
+                steps > 0
+                    && dx == rx * steps
+                    && dy == ry * steps
+                    && steps as usize <= self.reach(ray)
+            })
+            .copied()
+    }
+
+    /// Every cell from which a bomb would hit `target`, starting with the target's own cell.
+    pub fn attackers(&self, target: &Coord) -> Vec<Coord> {
+        // Rays come in opposite pairs, so the cells that can hit a target are exactly the ones a
+        // blast from the target would reach
+        self.blast(target)
+    }
+
+    /// Every cell from which a bomb would hit at least one of `targets`.
+    pub fn attack_points(&self, targets: &HashSet<Coord>) -> HashSet<Coord> {
+        targets.iter().flat_map(|target| self.attackers(target)).collect()
+    }
+}
@@ -0,0 +1,75 @@
+//! Boards, rules and solvers for Two Bombs, the puzzle where two bombs have to be planted so that
+//! their blasts destroy every target on the board.
+//!
+//! A board is a [`TBBoard`], read from and written as plain text: one line per row, with 'O' for
+//! a target, 'X' for a bomb, '#' for a wall and '.' for an empty cell. Its [`Geometry`] sets the
+//! size, the shape of the blasts and how the edges behave, and the solvers work from the targets
+//! and the geometry alone.
+//!
+//! ```
+//! use twobombs::{no_progress, CancelToken, TBBoard};
+//!
+//! let mut board: TBBoard = "O...\n..O.\n....\n".parse()?;
+//! let result = board.find_bombs(1, &CancelToken::new(), &no_progress);
+//! if let Some((b1, b2)) = result.solution() {
+//!     println!("Bombs at {} and {}", b1, b2);
+//! }
+//! print!("{}", board.to_text());
+//! # Ok::<(), twobombs::TBError>(())
+//! ```
+//!
+//! Boards too big to hold cell by cell, or with no edges at all, can be solved with a
+//! [`LineBoard`] instead.
+//!
+//! The terminal game is behind the `tui` feature, which is on by default. Without it the library
+//! builds with no terminal dependencies.
+
+/// Blast shapes, as the rays a blast travels along.
+pub mod attack;
+/// The bitboard solver backend the threaded solvers run on.
+pub mod bitboard;
+/// Proofs that a board can't be cleared with two bombs.
+pub mod certificate;
+/// Cancelling solver runs and reporting their progress.
+pub mod control;
+/// Cell coordinates.
+pub mod coord;
+/// The most targets a given number of bombs can clear.
+pub mod coverage;
+/// The daily puzzle and the stats kept on it.
+pub mod daily;
+/// Rating how hard a board is.
+pub mod difficulty;
+/// DIMACS CNF export and SAT model import.
+pub mod dimacs;
+pub mod errors;
+/// Random puzzle generation.
+pub mod generate;
+/// The line-crossing solver for huge and unbounded boards.
+pub mod geometric;
+/// Board size, blast rules and edge behaviour.
+pub mod geometry;
+/// Keeping track of whether a board is solvable as it's edited.
+pub mod live;
+/// Dropping the targets a unique solution doesn't need.
+pub mod minimize;
+/// Solvers spread across threads.
+pub mod parallel;
+/// Solver results and search statistics.
+pub mod result;
+/// The seeded random numbers behind generated boards.
+pub mod rng;
+mod symmetry;
+/// The terminal game.
+#[cfg(feature = "tui")]
+pub mod terminal;
+/// The board model and the original solvers.
+pub mod twobombs;
+
+pub use crate::control::{no_progress, CancelToken};
+pub use crate::coord::Coord;
+pub use crate::errors::TBError;
+pub use crate::geometric::{LineBoard, Point};
+pub use crate::geometry::Geometry;
+pub use crate::result::SolveResult;
+pub use crate::twobombs::{find_all_bombs, find_bombs, TBBoard, TBCell, TBGame};
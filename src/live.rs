@@ -0,0 +1,168 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+use crate::coord::Coord;
+use crate::geometry::Geometry;
+
+/// Whether the targets marked so far can be cleared with two bombs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Solvability {
+    NoTargets,
+    Unsolvable,
+    /// Number of distinct bomb pairs that clear the board
+    Solutions(usize),
+}
+
+impl fmt::Display for Solvability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Solvability::NoTargets => write!(f, "No targets marked"),
+            Solvability::Unsolvable => write!(f, "Unsolvable"),
+            Solvability::Solutions(1) => write!(f, "Solvable: 1 solution"),
+            Solvability::Solutions(count) => write!(f, "Solvable: {} solutions", count),
+        }
+    }
+}
+
+/// Solver that keeps up with targets being added and removed one at a time.
+///
+/// For each cell it tracks how many targets a bomb there would hit, which for the line patterns is
+/// the sum of the target counts along the lines through the cell. Toggling a target only touches
+/// the cells its blast reaches, and the counts rule out most bomb pairs before checking them. The
+/// answer is worked out once per change, so asking for it again costs nothing.
+#[derive(Debug, Clone)]
+pub struct LiveSolver {
+    geometry: Geometry,
+    // In row-major order, so the first target is the pivot
+    targets: BTreeSet<Coord>,
+    // indexed by Geometry::index
+    counts: Vec<usize>,
+    /// How many cells have each count, indexed by count, to find the highest counts without
+    /// sorting them all
+    cells_by_count: Vec<usize>,
+    solvability: Solvability,
+}
+
+impl LiveSolver {
+    pub fn new(targets: &HashSet<Coord>, geometry: &Geometry) -> Self {
+        let cell_count = geometry.width * geometry.height;
+        let mut live = LiveSolver {
+            geometry: geometry.clone(),
+            targets: BTreeSet::new(),
+            counts: vec![0; cell_count],
+            cells_by_count: vec![cell_count],
+            solvability: Solvability::NoTargets,
+        };
+        for target in targets {
+            live.insert(target);
+        }
+        live.solvability = live.solve();
+        live
+    }
+
+    /// Moves the cell at `index` from one count to another.
+    fn recount(&mut self, index: usize, count: usize) {
+        self.cells_by_count[self.counts[index]] -= 1;
+        if self.cells_by_count.len() <= count {
+            self.cells_by_count.resize(count + 1, 0);
+        }
+        self.cells_by_count[count] += 1;
+        self.counts[index] = count;
+    }
+
+    /// Adds `target` without working the answer out again, returning whether it's new.
+    fn insert(&mut self, target: &Coord) -> bool {
+        if !self.targets.insert(*target) {
+            return false;
+        }
+        for cell in self.geometry.attackers(target) {
+            let index = self.geometry.index(&cell);
+            self.recount(index, self.counts[index] + 1);
+        }
+        true
+    }
+
+    pub fn add(&mut self, target: &Coord) {
+        if self.insert(target) {
+            self.solvability = self.solve();
+        }
+    }
+
+    pub fn remove(&mut self, target: &Coord) {
+        if self.targets.remove(target) {
+            for cell in self.geometry.attackers(target) {
+                let index = self.geometry.index(&cell);
+                self.recount(index, self.counts[index] - 1);
+            }
+            self.solvability = self.solve();
+        }
+    }
+
+    fn count(&self, cell: &Coord) -> usize {
+        self.counts[self.geometry.index(cell)]
+    }
+
+    /// The two highest counts of any cells, which may be the same count twice.
+    fn best_two(&self) -> (usize, usize) {
+        let mut counts = self
+            .cells_by_count
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, cells)| **cells > 0);
+        match counts.next() {
+            Some((best, cells)) if *cells > 1 => (best, best),
+            Some((best, _)) => (best, counts.next().map_or(0, |(count, _)| count)),
+            None => (0, 0),
+        }
+    }
+
+    pub fn solvability(&self) -> Solvability {
+        self.solvability
+    }
+
+    fn solve(&self) -> Solvability {
+        let target_count = self.targets.len();
+        let pivot = match self.targets.iter().next() {
+            Some(pivot) => *pivot,
+            None => return Solvability::NoTargets,
+        };
+        let (best, second) = self.best_two();
+        if best + second < target_count {
+            // Even the two cells hitting the most targets can't cover them all
+            return Solvability::Unsolvable;
+        }
+
+        // One bomb of every solution hits the pivot
+        let mut solutions = 0;
+        for b1 in self.geometry.attackers(&pivot) {
+            let needed = target_count - self.count(&b1);
+            if needed > best {
+                continue;
+            }
+            let missed: Vec<&Coord> = self
+                .targets
+                .iter()
+                .filter(|target| !self.geometry.hits(&b1, target))
+                .collect();
+            let candidates: Vec<Coord> = match missed.first() {
+                Some(missed) => self.geometry.attackers(missed),
+                None => self.geometry.cells().collect(),
+            };
+            solutions += candidates
+                .iter()
+                .filter(|b2| {
+                    **b2 != b1
+                        && self.count(b2) >= needed
+                        && missed.iter().all(|target| self.geometry.hits(b2, target))
+                        // Pairs where both bombs hit the pivot are only counted in row-major order
+                        && !(self.geometry.hits(b2, &pivot) && (b2.y, b2.x) < (b1.y, b1.x))
+                })
+                .count();
+        }
+        match solutions {
+            0 => Solvability::Unsolvable,
+            count => Solvability::Solutions(count),
+        }
+    }
+}
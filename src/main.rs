@@ -1,19 +1,18 @@
-use clap::Arg;
+use clap::{Arg, SubCommand};
 use clap::{app_from_crate, crate_authors, crate_description, crate_name, crate_version};
 use flexi_logger::Logger;
 use log::debug;
 use tempdir::TempDir;
 
-mod errors;
 use anyhow::Result;
 
-mod coord;
-mod terminal;
-mod twobombs;
+use twobombs::{attack, terminal};
+
+mod cli;
 
 fn main() -> Result<()> {
     let td = TempDir::new(crate_name!())?;
-    println!("Logging to {}", td.path().display());
+    eprintln!("Logging to {}", td.path().display());
     let matches = app_from_crate!()
         .arg(
             Arg::with_name("debug")
@@ -21,6 +20,301 @@ fn main() -> Result<()> {
                 .multiple(true)
                 .help("Write extended debug log information to a temp file."),
         )
+        .arg(
+            Arg::with_name("threads")
+                .short("j")
+                .long("threads")
+                .takes_value(true)
+                .value_name("N")
+                .global(true)
+                .help("Number of solver threads, 0 for one per core (the default)."),
+        )
+        .arg(
+            Arg::with_name("pattern")
+                .short("p")
+                .long("pattern")
+                .takes_value(true)
+                .possible_values(attack::PATTERN_NAMES)
+                .global(true)
+                .help("Shape of the bomb blasts (default queen)."),
+        )
+        .arg(
+            Arg::with_name("radius")
+                .long("radius")
+                .takes_value(true)
+                .value_name("N")
+                .global(true)
+                .help("Stop blasts N cells away from the bomb (default no limit)."),
+        )
+        .arg(
+            Arg::with_name("torus")
+                .long("torus")
+                .global(true)
+                .help("Wrap blasts around the edges of the board."),
+        )
+        .arg(
+            Arg::with_name("hex")
+                .long("hex")
+                .global(true)
+                .conflicts_with("pattern")
+                .help("Play on a hex grid, with blasts along its three axes."),
+        )
+        .arg(
+            Arg::with_name("timeout")
+                .long("timeout")
+                .takes_value(true)
+                .value_name("SECS")
+                .global(true)
+                .help("Give up solving after SECS seconds (default no limit)."),
+        )
+        .arg(
+            Arg::with_name("width")
+                .long("width")
+                .takes_value(true)
+                .value_name("N")
+                .help("Number of columns on the board (default 23)."),
+        )
+        .arg(
+            Arg::with_name("height")
+                .long("height")
+                .takes_value(true)
+                .value_name("N")
+                .help("Number of rows on the board (default 23)."),
+        )
+        .subcommand(
+            SubCommand::with_name("solve")
+                .about("Solve a board read from a text file ('O' target, '.' empty).")
+                .arg(Arg::with_name("BOARD").required(true).help("Board file to solve."))
+                .arg(
+                    Arg::with_name("all")
+                        .short("a")
+                        .long("all")
+                        .help("List every solution instead of just the first one."),
+                )
+                .arg(
+                    Arg::with_name("certificate")
+                        .short("c")
+                        .long("certificate")
+                        .help("Print the full certificate when the board can't be solved."),
+                )
+                .arg(
+                    Arg::with_name("stats")
+                        .long("stats")
+                        .help("Print how much searching the solver did."),
+                )
+                .arg(
+                    Arg::with_name("max-bombs")
+                        .short("m")
+                        .long("max-bombs")
+                        .takes_value(true)
+                        .value_name("N")
+                        .conflicts_with("all")
+                        .help("Find the fewest bombs, up to N, that clear the board."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("cnf")
+                .about("Write a board as a DIMACS CNF instance for an external SAT solver.")
+                .arg(Arg::with_name("BOARD").required(true).help("Board file to export."))
+                .arg(
+                    Arg::with_name("bombs")
+                        .short("b")
+                        .long("bombs")
+                        .takes_value(true)
+                        .value_name("N")
+                        .help("Allow at most N bombs (default 2)."),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("Write the CNF to FILE instead of standard output."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("model")
+                .about("Read a SAT solver's model for a board's CNF and check the bombs it places.")
+                .arg(Arg::with_name("BOARD").required(true).help("Board file the CNF was made from."))
+                .arg(Arg::with_name("MODEL").required(true).help("SAT solver output file."))
+                .arg(
+                    Arg::with_name("bombs")
+                        .short("b")
+                        .long("bombs")
+                        .takes_value(true)
+                        .value_name("N")
+                        .help("The bomb limit the CNF was made with (default 2)."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("generate")
+                .about("Generate a random solvable board and write it out as a text file.")
+                .arg(
+                    Arg::with_name("width")
+                        .long("width")
+                        .takes_value(true)
+                        .value_name("N")
+                        .help("Number of columns on the board (default 23)."),
+                )
+                .arg(
+                    Arg::with_name("height")
+                        .long("height")
+                        .takes_value(true)
+                        .value_name("N")
+                        .help("Number of rows on the board (default 23)."),
+                )
+                .arg(
+                    Arg::with_name("targets")
+                        .short("t")
+                        .long("targets")
+                        .takes_value(true)
+                        .value_name("N")
+                        .help("Number of targets to place (default 8)."),
+                )
+                .arg(
+                    Arg::with_name("bombs")
+                        .short("b")
+                        .long("bombs")
+                        .takes_value(true)
+                        .value_name("N")
+                        .help("Number of bombs it should take to clear the board (default 2)."),
+                )
+                .arg(
+                    Arg::with_name("unique")
+                        .short("u")
+                        .long("unique")
+                        .help("Make the two-bomb solution unique, adding targets if needed."),
+                )
+                .arg(
+                    Arg::with_name("seed")
+                        .long("seed")
+                        .takes_value(true)
+                        .value_name("N")
+                        .help("Seed for the random board (default taken from the clock)."),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("Write the board to FILE instead of standard output."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("rate")
+                .about("Rate how hard boards are to solve, as easy, medium or hard.")
+                .arg(
+                    Arg::with_name("BOARD")
+                        .required(true)
+                        .multiple(true)
+                        .help("Board files to rate."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("minimize")
+                .about("Strip the targets a board's unique solution doesn't depend on.")
+                .arg(Arg::with_name("BOARD").required(true).help("Board file to minimize."))
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("Write the board to FILE instead of standard output."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("daily")
+                .about("Play today's puzzle, the same for everyone, and keep track of how it went.")
+                .arg(
+                    Arg::with_name("date")
+                        .long("date")
+                        .takes_value(true)
+                        .value_name("YYYY-MM-DD")
+                        .help("Play another day's puzzle instead of today's."),
+                )
+                .arg(
+                    Arg::with_name("stats-file")
+                        .long("stats-file")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("File to keep the results in (default ~/.twobombs-daily)."),
+                )
+                .arg(
+                    Arg::with_name("print")
+                        .long("print")
+                        .help("Write the puzzle out as a board file instead of playing it."),
+                )
+                .arg(
+                    Arg::with_name("history")
+                        .long("history")
+                        .conflicts_with("print")
+                        .help("Show how the daily puzzles have gone so far."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("sparse")
+                .about("Solve a huge or unbounded board given as a list of target points ('x,y').")
+                .arg(
+                    Arg::with_name("TARGETS")
+                        .required(true)
+                        .help("File with one target point per line."),
+                )
+                .arg(
+                    Arg::with_name("width")
+                        .long("width")
+                        .takes_value(true)
+                        .value_name("N")
+                        .requires("height")
+                        .help("Number of columns on the board (default no edges at all)."),
+                )
+                .arg(
+                    Arg::with_name("height")
+                        .long("height")
+                        .takes_value(true)
+                        .value_name("N")
+                        .requires("width")
+                        .help("Number of rows on the board (default no edges at all)."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("bench")
+                .about("Time the solver backends against each other on random and planted boards.")
+                .arg(
+                    Arg::with_name("size")
+                        .short("s")
+                        .long("size")
+                        .takes_value(true)
+                        .multiple(true)
+                        .value_name("N")
+                        .help("Board sizes to benchmark (default 23, 50 and 100)."),
+                )
+                .arg(
+                    Arg::with_name("targets")
+                        .short("t")
+                        .long("targets")
+                        .takes_value(true)
+                        .value_name("N")
+                        .help("Number of targets on each board (default 20)."),
+                )
+                .arg(
+                    Arg::with_name("rounds")
+                        .short("r")
+                        .long("rounds")
+                        .takes_value(true)
+                        .value_name("N")
+                        .help("Number of boards to time for each size (default 5)."),
+                )
+                .arg(
+                    Arg::with_name("seed")
+                        .long("seed")
+                        .takes_value(true)
+                        .value_name("N")
+                        .help("Seed for the random boards (default 0)."),
+                ),
+        )
         .get_matches();
 
     // Initialize logging
@@ -41,7 +335,28 @@ fn main() -> Result<()> {
 
     log_builder.start()?;
 
-    debug!("Starting game...");
-    terminal::play_game()?;
+    match matches.subcommand() {
+        ("solve", Some(sub_matches)) => cli::solve(sub_matches)?,
+        ("cnf", Some(sub_matches)) => cli::cnf(sub_matches)?,
+        ("model", Some(sub_matches)) => cli::model(sub_matches)?,
+        ("generate", Some(sub_matches)) => cli::generate(sub_matches)?,
+        ("rate", Some(sub_matches)) => cli::rate(sub_matches)?,
+        ("minimize", Some(sub_matches)) => cli::minimize(sub_matches)?,
+        ("daily", Some(sub_matches)) => {
+            if let Some(game) = cli::daily(sub_matches)? {
+                terminal::play_game(game, cli::threads(sub_matches)?, cli::timeout(sub_matches)?)?
+            }
+        }
+        ("sparse", Some(sub_matches)) => cli::sparse(sub_matches)?,
+        ("bench", Some(sub_matches)) => cli::bench(sub_matches)?,
+        _ => {
+            debug!("Starting game...");
+            terminal::play_game(
+                cli::new_game(&matches)?,
+                cli::threads(&matches)?,
+                cli::timeout(&matches)?,
+            )?;
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,64 @@
+use std::collections::HashSet;
+
+use crate::bitboard::BitBoard;
+use crate::control::{no_progress, CancelToken, Progress, ProgressFn};
+use crate::coord::Coord;
+use crate::errors::TBError;
+use crate::geometry::Geometry;
+use crate::parallel;
+
+/// Every bomb pair that clears `targets`, failing if `cancel` was cancelled before they were all
+/// found.
+fn solutions(
+    targets: &HashSet<Coord>,
+    geometry: &Geometry,
+    threads: usize,
+    cancel: &CancelToken,
+) -> Result<Vec<(Coord, Coord)>, TBError> {
+    let bitboard = BitBoard::new(targets, geometry);
+    let result = parallel::find_all_bombs(&bitboard, threads, cancel, &no_progress);
+    if result.cancelled {
+        return Err(TBError::TimedOut);
+    }
+    Ok(result.solutions)
+}
+
+/// Strips the targets that don't affect the answer from a board with a unique solution.
+///
+/// Targets are tried for removal one at a time in row-major order, and dropped whenever the board
+/// keeps exactly one solution without them. Taking targets away can only add solutions, so that
+/// solution is always the original pair, and a target that had to stay once can never go later:
+/// no single target of the result can be removed.
+///
+/// Each target tried is reported to `progress`. Fails with [`TBError::TimedOut`] if `cancel` is
+/// cancelled before every target has been tried.
+pub fn minimize(
+    targets: &HashSet<Coord>,
+    geometry: &Geometry,
+    threads: usize,
+    cancel: &CancelToken,
+    progress: ProgressFn,
+) -> Result<HashSet<Coord>, TBError> {
+    let count = solutions(targets, geometry, threads, cancel)?.len();
+    if count != 1 {
+        return Err(TBError::BadBoard(format!(
+            "only boards with exactly one solution can be minimized, this one has {}",
+            count
+        )));
+    }
+
+    let mut order: Vec<Coord> = targets.iter().cloned().collect();
+    order.sort();
+    let mut kept = targets.clone();
+    for (tried, target) in order.iter().enumerate() {
+        kept.remove(target);
+        if solutions(&kept, geometry, threads, cancel)?.len() != 1 {
+            kept.insert(*target);
+        }
+        progress(Progress {
+            tried: tried + 1,
+            total: order.len(),
+        });
+    }
+    Ok(kept)
+}
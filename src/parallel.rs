@@ -0,0 +1,170 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Instant;
+
+use crate::bitboard::BitBoard;
+use crate::control::{CancelToken, Progress, ProgressFn};
+use crate::coord::Coord;
+use crate::result::SolveResult;
+
+/// Number of worker threads to use when none was asked for: one per core.
+pub fn default_threads() -> usize {
+    thread::available_parallelism()
+        .map(|threads| threads.get())
+        .unwrap_or(1)
+}
+
+/// Runs `work` on `threads` workers, handing each one an interleaved share of the first-bomb
+/// candidates, and returns what each worker produced in worker order.
+fn split_first_bombs<T, F>(first_bombs: &[Coord], threads: usize, work: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&mut dyn Iterator<Item = (usize, &Coord)>) -> T + Sync,
+{
+    let threads = threads.max(1);
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|worker| {
+                let work = &work;
+                scope.spawn(move || {
+                    log::debug!("solver thread {} starting", worker);
+                    work(&mut first_bombs.iter().enumerate().skip(worker).step_by(threads))
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("Solver thread panicked!"))
+            .collect()
+    })
+}
+
+/// Running totals shared between the worker threads.
+#[derive(Default)]
+struct Counters {
+    first_bombs: AtomicUsize,
+    second_bombs: AtomicUsize,
+}
+
+impl Counters {
+    /// Adds one first bomb and its second bomb candidates to the totals, and reports the new
+    /// first bomb total out of `total`.
+    fn count(&self, second_bombs: usize, total: usize, progress: ProgressFn) {
+        let tried = self.first_bombs.fetch_add(1, Ordering::Relaxed) + 1;
+        self.second_bombs.fetch_add(second_bombs, Ordering::Relaxed);
+        progress(Progress { tried, total });
+    }
+
+    fn into_result(
+        self,
+        solutions: Vec<(Coord, Coord)>,
+        start: Instant,
+        exhaustive: bool,
+        cancelled: bool,
+    ) -> SolveResult {
+        SolveResult {
+            solutions,
+            first_bombs: self.first_bombs.into_inner(),
+            second_bombs: self.second_bombs.into_inner(),
+            elapsed: start.elapsed(),
+            exhaustive,
+            cancelled,
+        }
+    }
+}
+
+/// Looks for the first solution in the order `find_all_bombs` lists them, stopping each worker
+/// once a solution with an earlier first bomb than its own turns up or `cancel` is cancelled.
+///
+/// Which worker gets there first doesn't change the answer, so the same board always gives the
+/// same solution whatever the thread count. A run cancelled before every earlier first bomb was
+/// tried can't tell whether its solution is the first one, so it comes back cancelled with none.
+pub fn find_bombs(
+    bitboard: &BitBoard,
+    threads: usize,
+    cancel: &CancelToken,
+    progress: ProgressFn,
+) -> SolveResult {
+    let start = Instant::now();
+    let counters = Counters::default();
+    // Index of the earliest first bomb known to have a solution
+    let best = AtomicUsize::new(usize::MAX);
+    // Index of the earliest first bomb a worker gave up on because of `cancel`
+    let stopped = AtomicUsize::new(usize::MAX);
+    let first_bombs = bitboard.first_bomb_representatives();
+    let found = split_first_bombs(&first_bombs, threads, |candidates| {
+        for (index, b1) in candidates {
+            if index > best.load(Ordering::Relaxed) {
+                return None;
+            }
+            if cancel.is_cancelled() {
+                stopped.fetch_min(index, Ordering::Relaxed);
+                return None;
+            }
+            let candidates = bitboard.second_bomb_candidates(b1);
+            counters.count(candidates.len(), first_bombs.len(), progress);
+            if let Some(b2) = candidates
+                .iter()
+                .filter(|b2| bitboard.is_solution(b1, b2))
+                .min()
+            {
+                best.fetch_min(index, Ordering::Relaxed);
+                return Some((index, (*b1, *b2)));
+            }
+        }
+        None
+    })
+    .into_iter()
+    .flatten()
+    .min_by_key(|(index, _)| *index);
+    let cancelled = stopped.into_inner() < found.map_or(usize::MAX, |(index, _)| index);
+    let solution = found.filter(|_| !cancelled).map(|(_, solution)| solution);
+    counters.into_result(
+        solution.into_iter().collect(),
+        start,
+        solution.is_none() && !cancelled,
+        cancelled,
+    )
+}
+
+/// Collects every solution, ordered as `BitBoard::expand_solutions` describes: row-major by the
+/// first bomb, then the second. If `cancel` is cancelled the workers stop and the result only
+/// holds what was found until then.
+///
+/// Only one first bomb per symmetry orbit is searched, and the solutions found from those are
+/// mapped back through the board's symmetries.
+pub fn find_all_bombs(
+    bitboard: &BitBoard,
+    threads: usize,
+    cancel: &CancelToken,
+    progress: ProgressFn,
+) -> SolveResult {
+    let start = Instant::now();
+    let counters = Counters::default();
+    let first_bombs = bitboard.first_bomb_representatives();
+    let found: Vec<(Coord, Coord)> = split_first_bombs(&first_bombs, threads, |candidates| {
+        let mut solutions = Vec::new();
+        for (_, b1) in candidates {
+            if cancel.is_cancelled() {
+                break;
+            }
+            let candidates = bitboard.second_bomb_candidates(b1);
+            counters.count(candidates.len(), first_bombs.len(), progress);
+            // Keep the pairs `is_duplicate` would drop too, since their other ordering may start
+            // from a first bomb that was pruned
+            solutions.extend(
+                candidates
+                    .into_iter()
+                    .filter(|b2| bitboard.is_solution(b1, b2))
+                    .map(|b2| (*b1, b2)),
+            );
+        }
+        solutions
+    })
+    .into_iter()
+    .flatten()
+    .collect();
+    let solutions = bitboard.expand_solutions(&found);
+    let cancelled = cancel.is_cancelled();
+    counters.into_result(solutions, start, !cancelled, cancelled)
+}
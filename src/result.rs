@@ -0,0 +1,44 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::coord::Coord;
+
+/// The outcome of a solver run, along with some numbers on how much searching it took.
+#[derive(Debug, Clone, Default)]
+pub struct SolveResult {
+    pub solutions: Vec<(Coord, Coord)>,
+    /// Number of first-bomb candidates tried
+    pub first_bombs: usize,
+    /// Number of second-bomb candidates tried, across all first bombs
+    pub second_bombs: usize,
+    pub elapsed: Duration,
+    /// Whether every candidate was tried, rather than stopping at the first solution
+    pub exhaustive: bool,
+    /// Whether the search was cancelled or timed out before it was done
+    pub cancelled: bool,
+}
+
+impl SolveResult {
+    pub fn solution(&self) -> Option<(Coord, Coord)> {
+        self.solutions.first().cloned()
+    }
+}
+
+impl fmt::Display for SolveResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Tried {} first and {} second bomb candidates in {:.3?} ({})",
+            self.first_bombs,
+            self.second_bombs,
+            self.elapsed,
+            if self.cancelled {
+                "cancelled"
+            } else if self.exhaustive {
+                "exhaustive search"
+            } else {
+                "stopped early"
+            }
+        )
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+
+use crate::coord::Coord;
+use crate::geometry::{Geometry, Grid};
+
+/// One of the eight symmetries of a square, as a mapping of the board onto itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    Identity,
+    RotateQuarter,
+    RotateHalf,
+    RotateThreeQuarters,
+    /// Mirror left to right
+    FlipColumns,
+    /// Mirror top to bottom
+    FlipRows,
+    /// Mirror along the diagonal through the top left corner
+    Transpose,
+    /// Mirror along the diagonal through the top right corner
+    AntiTranspose,
+}
+
+const SYMMETRIES: &[Symmetry] = &[
+    Symmetry::Identity,
+    Symmetry::RotateQuarter,
+    Symmetry::RotateHalf,
+    Symmetry::RotateThreeQuarters,
+    Symmetry::FlipColumns,
+    Symmetry::FlipRows,
+    Symmetry::Transpose,
+    Symmetry::AntiTranspose,
+];
+
+impl Symmetry {
+    /// Whether the symmetry swaps rows and columns, so only maps square boards onto themselves.
+    fn needs_square(self) -> bool {
+        matches!(
+            self,
+            Symmetry::RotateQuarter
+                | Symmetry::RotateThreeQuarters
+                | Symmetry::Transpose
+                | Symmetry::AntiTranspose
+        )
+    }
+
+    /// Where the symmetry takes a step of `(dx, dy)`.
+    fn apply_ray(self, (dx, dy): (isize, isize)) -> (isize, isize) {
+        match self {
+            Symmetry::Identity => (dx, dy),
+            Symmetry::RotateQuarter => (-dy, dx),
+            Symmetry::RotateHalf => (-dx, -dy),
+            Symmetry::RotateThreeQuarters => (dy, -dx),
+            Symmetry::FlipColumns => (-dx, dy),
+            Symmetry::FlipRows => (dx, -dy),
+            Symmetry::Transpose => (dy, dx),
+            Symmetry::AntiTranspose => (-dy, -dx),
+        }
+    }
+
+    /// Where the symmetry takes the cell at `coord`.
+    pub fn apply(self, coord: &Coord, geometry: &Geometry) -> Coord {
+        let (x, y) = (coord.x, coord.y);
+        let (last_x, last_y) = (geometry.width - 1, geometry.height - 1);
+        let (x, y) = match self {
+            Symmetry::Identity => (x, y),
+            Symmetry::RotateQuarter => (last_y - y, x),
+            Symmetry::RotateHalf => (last_x - x, last_y - y),
+            Symmetry::RotateThreeQuarters => (y, last_x - x),
+            Symmetry::FlipColumns => (last_x - x, y),
+            Symmetry::FlipRows => (x, last_y - y),
+            Symmetry::Transpose => (y, x),
+            Symmetry::AntiTranspose => (last_y - y, last_x - x),
+        };
+        Coord { x, y }
+    }
+
+    /// Whether the symmetry leaves the blast rules alone: every ray maps onto a ray of the
+    /// pattern, and every wall onto a wall.
+    fn preserves(self, geometry: &Geometry) -> bool {
+        let rays = geometry.pattern.rays();
+        (!self.needs_square() || geometry.width == geometry.height)
+            && rays.iter().all(|ray| rays.contains(&self.apply_ray(*ray)))
+            && geometry
+                .walls
+                .iter()
+                .all(|wall| geometry.walls.contains(&self.apply(wall, geometry)))
+    }
+}
+
+/// Every symmetry of the board that maps `targets` onto themselves, starting with the identity.
+///
+/// Solutions come in whole orbits under these, so a solver only has to look at one first bomb per
+/// orbit. Hex boards only get the identity.
+pub fn find_symmetries(targets: &HashSet<Coord>, geometry: &Geometry) -> Vec<Symmetry> {
+    if geometry.grid == Grid::Hex || geometry.width == 0 || geometry.height == 0 {
+        return vec![Symmetry::Identity];
+    }
+    SYMMETRIES
+        .iter()
+        .copied()
+        .filter(|symmetry| symmetry.preserves(geometry))
+        .filter(|symmetry| {
+            targets
+                .iter()
+                .all(|target| targets.contains(&symmetry.apply(target, geometry)))
+        })
+        .collect()
+}
+
+/// Whether `b1` comes first in row-major order among the cells `symmetries` map it to that still
+/// hit `pivot`, so it's the one first bomb of its orbit worth trying.
+pub fn is_representative(
+    b1: &Coord,
+    pivot: &Coord,
+    symmetries: &[Symmetry],
+    geometry: &Geometry,
+) -> bool {
+    symmetries.iter().all(|symmetry| {
+        let image = symmetry.apply(b1, geometry);
+        !geometry.hits(&image, pivot) || b1 <= &image
+    })
+}
+
+/// The target every solver pivots on. One bomb of every solution has to hit it, so only the cells
+/// hitting it need trying as the first bomb.
+///
+/// Any target would do, so this picks the one leaving the fewest first bombs once symmetric ones
+/// are pruned, and the earliest in row-major order on a tie. Solvers all pivoting on the same
+/// target is what makes them list the same solutions in the same order.
+pub fn choose_pivot(
+    targets: &HashSet<Coord>,
+    geometry: &Geometry,
+    symmetries: &[Symmetry],
+) -> Option<Coord> {
+    let mut targets: Vec<Coord> = targets.iter().cloned().collect();
+    targets.sort();
+    targets.into_iter().min_by_key(|pivot| {
+        geometry
+            .attackers(pivot)
+            .iter()
+            .filter(|b1| is_representative(b1, pivot, symmetries, geometry))
+            .count()
+    })
+}
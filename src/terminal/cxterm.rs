@@ -1,7 +1,10 @@
 use std::io::Write;
+use std::time::Duration;
 
 use crossterm::{cursor, event, style, terminal};
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, MouseEvent, MouseEventKind};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
 use crossterm::QueueableCommand;
 
 use crate::coord::Coord;
@@ -9,7 +12,7 @@ use crate::errors::{TBError, Result};
 use crate::terminal::settings::RenderSettings;
 use crate::terminal::GameAction;
 
-const MAX_MSGLOG_LINES: usize = 4;
+const MAX_MSGLOG_LINES: usize = 8;
 
 pub struct CxTerm<W: Write> {
     writer: W,
@@ -120,18 +123,26 @@ impl<W: Write> CxTerm<W> {
         self.active_cell.unwrap_or_default()
     }
 
-    pub fn update_active_board_cell(&mut self, update: fn(&Coord) -> Coord) {
+    /// Moves the active cell by `dx` columns and `dy` rows, wrapping around the board edges on a
+    /// torus and stopping at them otherwise.
+    pub fn update_active_board_cell(&mut self, dx: isize, dy: isize) {
         let active = self.get_active_board_cell();
-        let board_size = self.settings.get_board_size();
-        let mut next = update(&active);
+        let board_width = self.settings.get_board_width() as isize;
+        let board_height = self.settings.get_board_height() as isize;
+        let x = active.x as isize + dx;
+        let y = active.y as isize + dy;
 
-        if next.x > board_size - 1 {
-            next.x = board_size - 1;
-        }
-
-        if next.y > board_size - 1 {
-            next.y = board_size - 1;
-        }
+        let next = if self.settings.get_board_wraps() {
+            Coord {
+                x: x.rem_euclid(board_width) as usize,
+                y: y.rem_euclid(board_height) as usize,
+            }
+        } else {
+            Coord {
+                x: x.clamp(0, board_width - 1) as usize,
+                y: y.clamp(0, board_height - 1) as usize,
+            }
+        };
 
         self.active_cell = Some(next);
     }
@@ -143,58 +154,124 @@ impl<W: Write> CxTerm<W> {
     pub fn get_game_action(&mut self) -> Result<GameAction> {
         let mut action: Option<GameAction> = None;
         while action.is_none() {
-            let active_cell = self.get_active_board_cell();
-            let term_coord = self.settings.cell_coord_to_term_coord(&active_cell);
-            self.blink_cursor(&term_coord)?;
+            self.show_active_board_cell()?;
+            let event = self.get_input_event();
+            action = self.event_to_game_action(event)?;
+        }
+        self.hide_cursor()?;
+        self.commit()?;
+        action.ok_or(TBError::InvalidGameInput)
+    }
+
+    /// Like `get_game_action`, but gives up and returns `None` if no action comes in within
+    /// `timeout`.
+    pub fn poll_game_action(&mut self, timeout: Duration) -> Result<Option<GameAction>> {
+        self.show_active_board_cell()?;
+        if !event::poll(timeout)? {
+            return Ok(None);
+        }
+        let event = self.get_input_event();
+        let action = self.event_to_game_action(event)?;
+        if action.is_some() {
+            self.hide_cursor()?;
             self.commit()?;
+        }
+        Ok(action)
+    }
 
-            match self.get_input_event() {
-                Ok(Event::Key(KeyEvent { code: KeyCode::Left, kind: KeyEventKind::Press, .. })) => {
-                    self.update_active_board_cell(|coord| *coord - Coord { x: 1, y: 0 });
-                },
-                Ok(Event::Key(KeyEvent { code: KeyCode::Right, kind: KeyEventKind::Press, .. })) => {
-                    self.update_active_board_cell(|coord| *coord + Coord { x: 1, y: 0 });
-                },
-                Ok(Event::Key(KeyEvent { code: KeyCode::Up, kind: KeyEventKind::Press, .. })) => {
-                    self.update_active_board_cell(|coord| *coord - Coord { x: 0, y: 1 });
-                },
-                Ok(Event::Key(KeyEvent { code: KeyCode::Down, kind: KeyEventKind::Press, .. })) => {
-                    self.update_active_board_cell(|coord| *coord + Coord { x: 0, y: 1 });
-                }
-                Ok(Event::Key(KeyEvent { code: KeyCode::Enter, kind: KeyEventKind::Press, .. })) => {
-                    action = Some(GameAction::MarkTarget(active_cell));
-                },
-                Ok(Event::Key(KeyEvent { code: KeyCode::Char(' '), kind: KeyEventKind::Press, .. })) => {
-                    action = Some(GameAction::MarkTarget(active_cell));
-                },
-                Ok(Event::Key(KeyEvent { code: KeyCode::Char('s'), kind: KeyEventKind::Press, .. })) => {
-                    action = Some(GameAction::Solve);
-                },
-                Ok(Event::Key(KeyEvent { code: KeyCode::Char('S'), kind: KeyEventKind::Press, .. })) => {
-                    action = Some(GameAction::Solve);
-                },
-                Ok(Event::Key(KeyEvent { code: KeyCode::Char('q'), kind: KeyEventKind::Press, .. })) => {
-                    action = Some(GameAction::Quit);
-                },
-                Ok(Event::Key(KeyEvent { code: KeyCode::Char('Q'), kind: KeyEventKind::Press, .. })) => {
-                    action = Some(GameAction::Quit);
-                },
-                Ok(Event::Key(KeyEvent { code: KeyCode::Esc, kind: KeyEventKind::Press, .. })) => {
-                    action = Some(GameAction::Quit);
-                },
-                Ok(Event::Mouse(MouseEvent { kind: MouseEventKind::Down(_), column: term_x, row: term_y, .. })) => {
-                    let game_coord = self.settings.term_coord_to_cell_coord(&Coord {
-                        x: term_x as usize,
-                        y: term_y as usize,
-                    });
+    fn show_active_board_cell(&mut self) -> Result<()> {
+        let active_cell = self.get_active_board_cell();
+        let term_coord = self.settings.cell_coord_to_term_coord(&active_cell);
+        self.blink_cursor(&term_coord)?;
+        self.commit()
+    }
+
+    /// The game action `event` stands for, if any. Events that only move the cursor don't.
+    fn event_to_game_action(&mut self, event: Result<Event>) -> Result<Option<GameAction>> {
+        let active_cell = self.get_active_board_cell();
+        let mut action: Option<GameAction> = None;
+        match event {
+            Ok(Event::Key(KeyEvent { code: KeyCode::Left, kind: KeyEventKind::Press, .. })) => {
+                self.update_active_board_cell(-1, 0);
+            },
+            Ok(Event::Key(KeyEvent { code: KeyCode::Right, kind: KeyEventKind::Press, .. })) => {
+                self.update_active_board_cell(1, 0);
+            },
+            Ok(Event::Key(KeyEvent { code: KeyCode::Up, kind: KeyEventKind::Press, .. })) => {
+                self.update_active_board_cell(0, -1);
+            },
+            Ok(Event::Key(KeyEvent { code: KeyCode::Down, kind: KeyEventKind::Press, .. })) => {
+                self.update_active_board_cell(0, 1);
+            }
+            Ok(Event::Key(KeyEvent { code: KeyCode::Enter, kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::MarkTarget(active_cell));
+            },
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char(' '), kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::MarkTarget(active_cell));
+            },
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char('s'), kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::Solve);
+            },
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char('S'), kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::Solve);
+            },
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char('n'), kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::NextSolution);
+            },
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char('N'), kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::NextSolution);
+            },
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char('h'), kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::Hint);
+            }
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char('H'), kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::Hint);
+            }
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char('r'), kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::NewPuzzle);
+            }
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char('R'), kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::NewPuzzle);
+            }
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char('m'), kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::Minimize);
+            }
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char('M'), kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::Minimize);
+            }
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char('q'), kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::Quit);
+            },
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char('Q'), kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::Quit);
+            },
+            Ok(Event::Key(KeyEvent { code: KeyCode::Esc, kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::Cancel);
+            },
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char('w'), kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::MarkWall(active_cell));
+            },
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char('W'), kind: KeyEventKind::Press, .. })) => {
+                action = Some(GameAction::MarkWall(active_cell));
+            },
+            Ok(Event::Mouse(MouseEvent { kind: MouseEventKind::Down(button), column: term_x, row: term_y, modifiers })) => {
+                let game_coord = self.settings.term_coord_to_cell_coord(&Coord {
+                    x: term_x as usize,
+                    y: term_y as usize,
+                });
+                // Clicks outside the board are ignored
+                if let Some(game_coord) = game_coord {
                     self.hide_cursor()?;
-                    action = Some(GameAction::MarkTarget(game_coord));
+                    // Right click, or Ctrl + click, for walls
+                    if button == MouseButton::Right || modifiers.contains(KeyModifiers::CONTROL) {
+                        action = Some(GameAction::MarkWall(game_coord));
+                    } else {
+                        action = Some(GameAction::MarkTarget(game_coord));
+                    }
                 }
-                _ => (),
             }
+            _ => (),
         }
-        self.hide_cursor()?;
-        self.commit()?;
-        action.ok_or(TBError::InvalidGameInput)
+        Ok(action)
     }
 }
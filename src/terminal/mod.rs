@@ -1,9 +1,23 @@
 use log::debug;
+use std::collections::HashSet;
 use std::io::Write;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
 
+use crate::certificate::Certificate;
+use crate::control::{CancelToken, Progress};
 use crate::coord::Coord;
-use crate::errors::Result;
-use crate::twobombs::TBGame;
+use crate::coverage::Coverage;
+use crate::daily::{format_duration, DailyGame, Outcome as DailyOutcome};
+use crate::difficulty::{rate_solved, Rating};
+use crate::errors::{Result, TBError};
+use crate::generate::generate_unique;
+use crate::geometry::{Grid, Topology};
+use crate::minimize::minimize;
+use crate::result::SolveResult;
+use crate::rng::clock_seed;
+use crate::twobombs::{TBBoard, TBGame};
 
 mod settings;
 use settings::RenderSettings;
@@ -11,24 +25,42 @@ use settings::RenderSettings;
 mod cxterm;
 use cxterm::CxTerm;
 
+// Upper bound for the minimum bomb search when two bombs aren't enough
+const MAX_BOMBS: usize = 4;
+
+// How often to check on the background solver while waiting for input
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// How many targets a new random puzzle starts out with
+const RANDOM_PUZZLE_TARGETS: usize = 8;
+
 pub enum GameAction {
     MarkTarget(Coord),
+    MarkWall(Coord),
     Solve,
+    NextSolution,
+    Hint,
+    NewPuzzle,
+    Minimize,
+    Cancel,
     Quit,
 }
 
-const INSTRUCTIONS: &str = r#"Press 'S', or 's' to solve.
-Press 'Q', 'q', or <Esc> to quit.
+const INSTRUCTIONS: &str = r#"Press 'S', or 's' to solve, 'H', or 'h' for a hint, or 'M', or 'm' to drop unneeded targets.
+Press 'N', or 'n' to show the next solution, or 'R', or 'r' for a new random puzzle.
+Press 'Q', 'q', or <Esc> to quit, or <Esc> while solving to cancel.
 To make a move:
 1. Mouse click in square, or
-2. Arrows to move and <Space> or <Enter> to select."#;
+2. Arrows to move and <Space> or <Enter> to select.
+Right click, Ctrl + click, or 'W' places a wall instead.
+Targets shown as '!' are left standing by the bombs."#;
 
 pub fn render_board<W: Write>(term: &mut CxTerm<W>, game: &mut TBGame, title: &str) -> Result<()> {
-    let board_size = game.board.get_size();
+    let board_height = game.board.get_height();
     // Redraw board state
     term.write_title(title)?;
 
-    for board_row in 0..board_size {
+    for board_row in 0..board_height {
         let rendered_board_row: usize = board_row * 2;
         debug!(
             "Rendering board row: {} ({})",
@@ -37,24 +69,209 @@ pub fn render_board<W: Write>(term: &mut CxTerm<W>, game: &mut TBGame, title: &s
         if board_row == 0 {
             term.write_rendered_board_row(rendered_board_row, &game.board.render_board_top())?;
         } else {
-            term.write_rendered_board_row(rendered_board_row, &game.board.render_board_row_sep())?;
+            term.write_rendered_board_row(rendered_board_row, &game.board.render_board_row_sep(board_row))?;
         }
         term.write_rendered_board_row(
             rendered_board_row + 1,
             &game.board.render_board_row(board_row),
         )?;
     }
-    term.write_rendered_board_row(board_size * 2, &game.board.render_board_bottom())?;
+    term.write_rendered_board_row(board_height * 2, &game.board.render_board_bottom())?;
 
     term.commit()?;
     Ok(())
 }
 
-pub fn play_game() -> Result<()> {
-    let board_size: usize = 23;
-    let mut game = TBGame::new_anysize(board_size);
+/// Puts a bomb down at `coord` in the daily puzzle, or picks it back up if there's one there
+/// already, and checks the answer once both bombs are down. Returns what to tell the player.
+fn place_guess(
+    board: &mut TBBoard,
+    daily: &mut DailyGame,
+    hints_used: usize,
+    coord: Coord,
+) -> Result<String> {
+    if let Some(index) = daily.guesses.iter().position(|guess| *guess == coord) {
+        daily.guesses.remove(index);
+    } else if daily.guesses.len() == 2 {
+        return Ok(String::from(
+            "Both bombs are down, select one to pick it back up.",
+        ));
+    } else {
+        daily.guesses.push(coord);
+    }
+    board.place_bombs(&daily.guesses);
+    if daily.guesses.len() < 2 {
+        return Ok(format!("{} of 2 bombs placed.", daily.guesses.len()));
+    }
+
+    let geometry = board.geometry();
+    let standing = board
+        .targets()
+        .iter()
+        .filter(|target| !daily.guesses.iter().any(|bomb| geometry.hits(bomb, target)))
+        .count();
+    if standing > 0 {
+        return Ok(format!(
+            "Those bombs leave {} targets standing, shown as '!'.\nSelect a bomb to pick it back up.",
+            standing
+        ));
+    }
+    let counted = !daily.finished;
+    daily.finish(DailyOutcome::Solved, hints_used)?;
+    let mut msg = format!("Solved in {}!", format_duration(daily.elapsed()));
+    if !counted {
+        msg.push_str(" Only the first finish of the day counts.");
+    }
+    msg.push_str(&format!("\n{}", daily.stats));
+    Ok(msg)
+}
+
+/// Records an unfinished daily puzzle on the way out, if that's what's being played.
+fn leave_daily(game: &mut TBGame) -> Result<()> {
+    match &mut game.daily {
+        Some(daily) => daily.finish(DailyOutcome::Unfinished, game.hints_used),
+        None => Ok(()),
+    }
+}
+
+/// Everything the background solver worked out about the board.
+struct Outcome {
+    result: SolveResult,
+    certificate: Option<Certificate>,
+    min_bombs: Option<Vec<Coord>>,
+    coverage: Option<Coverage>,
+    /// How hard the board is, once every solution has been found
+    rating: Option<Rating>,
+    /// The targets left once the ones the unique solution doesn't need are gone
+    minimized: Option<std::result::Result<HashSet<Coord>, TBError>>,
+}
+
+/// What the background solver is asked to work out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Search {
+    /// Every solution, or why there aren't any
+    Solve,
+    /// Just the first solution
+    Hint,
+    /// The targets the unique solution needs
+    Minimize,
+}
+
+enum SolverEvent {
+    Progress(Progress),
+    Done(Box<Outcome>),
+}
+
+/// Solves or minimizes a copy of the board on its own thread, so the terminal stays responsive.
+fn spawn_solver(
+    mut board: TBBoard,
+    search: Search,
+    threads: usize,
+    cancel: CancelToken,
+) -> Receiver<SolverEvent> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let progress_sender = sender.clone();
+        let progress = move |progress| {
+            let _ = progress_sender.send(SolverEvent::Progress(progress));
+        };
+        let mut outcome = Outcome {
+            result: SolveResult::default(),
+            certificate: None,
+            min_bombs: None,
+            coverage: None,
+            rating: None,
+            minimized: None,
+        };
+        match search {
+            Search::Solve => outcome.result = board.find_all_bombs(threads, &cancel, &progress),
+            Search::Hint => outcome.result = board.find_bombs(threads, &cancel, &progress),
+            Search::Minimize => {
+                outcome.minimized = Some(minimize(
+                    &board.targets(),
+                    &board.geometry(),
+                    threads,
+                    &cancel,
+                    &progress,
+                ))
+            }
+        }
+        if search == Search::Solve && !outcome.result.cancelled {
+            outcome.rating = rate_solved(
+                &board.targets(),
+                &board.geometry(),
+                &outcome.result.solutions,
+            );
+        }
+        if search == Search::Solve
+            && outcome.result.solutions.is_empty()
+            && !outcome.result.cancelled
+        {
+            // Explain why two bombs can't do it before looking for how many can
+            outcome.certificate = board.find_certificate();
+            outcome.min_bombs = board.find_min_bombs(MAX_BOMBS, &cancel);
+            if outcome.min_bombs.is_none() && !cancel.is_cancelled() {
+                // Show how close two bombs get instead
+                outcome.coverage = Some(board.find_max_coverage(2));
+            }
+        }
+        let _ = sender.send(SolverEvent::Done(Box::new(outcome)));
+    });
+    receiver
+}
+
+/// Shows the background solver's progress until it's done, cancelling it on <Esc>. Returns
+/// `None` if the player quit instead.
+fn wait_for_solver<W: Write>(
+    term: &mut CxTerm<W>,
+    receiver: &Receiver<SolverEvent>,
+    search: Search,
+    cancel: &CancelToken,
+) -> Result<Option<Box<Outcome>>> {
+    loop {
+        match term.poll_game_action(POLL_INTERVAL)? {
+            Some(GameAction::Cancel) => cancel.cancel(),
+            Some(GameAction::Quit) => {
+                cancel.cancel();
+                return Ok(None);
+            }
+            // The board can't change under the solver
+            _ => (),
+        }
+        let mut latest = None;
+        loop {
+            match receiver.try_recv() {
+                Ok(SolverEvent::Progress(progress)) => latest = Some(progress),
+                Ok(SolverEvent::Done(outcome)) => return Ok(Some(outcome)),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Err(TBError::SolverFailed),
+            }
+        }
+        if let Some(progress) = latest {
+            let state = if cancel.is_cancelled() {
+                "Cancelling"
+            } else if search == Search::Minimize {
+                "Minimizing"
+            } else {
+                "Solving"
+            };
+            let progress = match search {
+                // Minimizing goes target by target rather than by first bomb
+                Search::Minimize => {
+                    format!("{} of {} targets tried", progress.tried, progress.total)
+                }
+                Search::Solve | Search::Hint => progress.to_string(),
+            };
+            term.write_msglog(&format!("{}... {}\nPress <Esc> to cancel.", state, progress))?;
+            term.commit()?;
+        }
+    }
+}
 
-    let mut term = CxTerm::new(RenderSettings::new(2, 4, board_size), std::io::stdout())?;
+pub fn play_game(mut game: TBGame, threads: usize, timeout: Option<Duration>) -> Result<()> {
+    let geometry = game.board.geometry();
+    let settings = RenderSettings::new(2, 4, &geometry);
+    let mut term = CxTerm::new(settings, std::io::stdout())?;
     debug!("Resetting display");
     term.reset_display()?;
 
@@ -63,26 +280,300 @@ pub fn play_game() -> Result<()> {
     // We want this to be written once, and not refreshed with each loop
     term.write_msglog(INSTRUCTIONS)?;
 
-    let mut title = String::from("Welcome to TwoBombs!");
+    let mut title = match geometry.grid {
+        Grid::Square => format!(
+            "Welcome to TwoBombs! Bombs blast like a {}",
+            geometry.pattern.name()
+        ),
+        Grid::Hex => String::from("Welcome to hex TwoBombs! Bombs blast along all three axes"),
+    };
+    match geometry.radius {
+        Some(radius) => title.push_str(&format!(", up to {} cells away.", radius)),
+        None => title.push('.'),
+    }
+    if geometry.topology == Topology::Torus {
+        title.push_str(" The board wraps around at the edges.");
+    }
+    if let Some(daily) = &game.daily {
+        title = format!(
+            "Daily puzzle for {}: select the two cells whose bombs clear all {} targets.",
+            daily.date,
+            game.board.targets().len()
+        );
+    }
+    // Every solution to the board as it was when last solved, along with which one is shown
+    let mut solutions: Option<(SolveResult, usize)> = None;
+    // The solution hints are being given from, and how many of its bombs are revealed
+    let mut hint: Option<((Coord, Coord), usize)> = None;
+    // How hard the board is, worked out along with its solutions and shown until it changes
+    let mut rating: Option<Rating> = None;
     loop {
         render_board(&mut term, &mut game, title.as_str())?;
+        let mut status = game.board.solvability().to_string();
+        if let Some(rating) = &rating {
+            status.push_str(&format!(" | {}", rating));
+        }
+        if game.hints_used > 0 {
+            status.push_str(&format!(" | Hints used: {}", game.hints_used));
+        }
+        term.write_status(&status)?;
+        term.commit()?;
         match term.get_game_action()? {
+            GameAction::MarkTarget(coord) if game.daily.is_some() => {
+                let TBGame {
+                    board,
+                    daily,
+                    hints_used,
+                } = &mut game;
+                if let Some(daily) = daily {
+                    let msg = place_guess(board, daily, *hints_used, coord)?;
+                    term.write_msglog(&msg)?;
+                    term.commit()?;
+                }
+            }
+            GameAction::MarkWall(_) | GameAction::NewPuzzle | GameAction::Minimize
+                if game.daily.is_some() =>
+            {
+                term.write_msglog("The daily puzzle's targets can't be changed.")?;
+                term.commit()?;
+            }
             GameAction::MarkTarget(coord) => {
+                // The last solution or hint doesn't go with the changed board
+                game.board.clear_bombs();
                 game.board.mark_target(&coord);
-                title = String::from(&format!("{} fields marked", game.board.cells.len()));
+                solutions = None;
+                rating = None;
+                hint = None;
+                title = format!("{} targets marked", game.board.targets().len());
+                term.commit()?;
+            }
+            GameAction::MarkWall(coord) => {
+                game.board.clear_bombs();
+                game.board.mark_wall(&coord);
+                solutions = None;
+                rating = None;
+                hint = None;
+                title = format!("{} walls placed", game.board.walls().len());
                 term.commit()?;
             }
             GameAction::Solve => {
-                if let Some(_) = game.board.find_bombs() {
+                let cancel = CancelToken::new().with_timeout(timeout);
+                title = String::from("Solving...");
+                render_board(&mut term, &mut game, title.as_str())?;
+                term.write_msglog("Solving...\nPress <Esc> to cancel.")?;
+                term.commit()?;
+                let receiver =
+                    spawn_solver(game.board.clone(), Search::Solve, threads, cancel.clone());
+                let outcome = match wait_for_solver(&mut term, &receiver, Search::Solve, &cancel)? {
+                    Some(outcome) => outcome,
+                    None => return leave_daily(&mut game),
+                };
+                let result = outcome.result;
+                hint = None;
+                rating = outcome.rating;
+                if result.cancelled {
+                    // Whatever was found before stopping is only part of the list, so don't
+                    // offer it as the board's solutions
+                    solutions = None;
+                    title = String::from("Solving cancelled");
+                    term.write_msglog(&format!("Solving cancelled.\n{}", result))?;
+                } else if let Some(bombs) = result.solution() {
+                    game.board.place_bombs(&[bombs.0, bombs.1]);
                     title = String::from("Solution found!");
-                    term.write_msglog("Solution found!")?;
+                    term.write_msglog(&format!(
+                        "Solution found! Bombs at {} and {}\n{} solutions in total, press 'N' to \
+                         see the next one.\n{}",
+                        bombs.0,
+                        bombs.1,
+                        result.solutions.len(),
+                        result
+                    ))?;
+                    if let Some(daily) = &mut game.daily {
+                        daily.guesses = vec![bombs.0, bombs.1];
+                        daily.finish(DailyOutcome::GaveUp, game.hints_used)?;
+                    }
+                    solutions = Some((result, 0));
                 } else {
-                    title = String::from("No solution found!");
-                    term.write_msglog("No solution found!")?;
+                    solutions = None;
+                    let mut msg = String::from("No two-bomb solution found!\n");
+                    if let Some(certificate) = &outcome.certificate {
+                        msg.push_str(&format!("{}\n", certificate));
+                    }
+                    if let Some(bombs) = &outcome.min_bombs {
+                        game.board.place_bombs(bombs);
+                        title = format!("No two-bomb solution, {} bombs needed", bombs.len());
+                        let bombs: Vec<String> =
+                            bombs.iter().map(|bomb| bomb.to_string()).collect();
+                        msg.push_str(&format!("Bombs at {} clear the board.", bombs.join(" ")));
+                    } else {
+                        title = String::from("No solution found!");
+                        msg.push_str(&format!("No solution found with up to {} bombs!", MAX_BOMBS));
+                        // Mark the targets the best two bombs leave standing
+                        if let Some(coverage) = &outcome.coverage {
+                            game.board.place_bombs(&coverage.bombs);
+                            msg.push_str(&format!("\n{}", coverage));
+                        }
+                    }
+                    msg.push_str(&format!("\n{}", result));
+                    term.write_msglog(&msg)?;
+                }
+                term.commit()?;
+            }
+            GameAction::NextSolution => {
+                match solutions.as_mut() {
+                    Some((result, shown)) => {
+                        // Cycle back around to the first solution after the last one
+                        *shown = (*shown + 1) % result.solutions.len();
+                        let bombs = result.solutions[*shown];
+                        game.board.place_bombs(&[bombs.0, bombs.1]);
+                        title = format!("Solution {} of {}", *shown + 1, result.solutions.len());
+                        term.write_msglog(&format!(
+                            "Solution {} of {}: bombs at {} and {}",
+                            *shown + 1,
+                            result.solutions.len(),
+                            bombs.0,
+                            bombs.1
+                        ))?;
+                    }
+                    None => term.write_msglog("Press 'S' to solve the board first.")?,
+                }
+                term.commit()?;
+            }
+            GameAction::Hint => {
+                match hint {
+                    Some(((b1, b2), 1)) => {
+                        game.board.place_bombs(&[b1, b2]);
+                        game.hints_used += 1;
+                        if let Some(daily) = &mut game.daily {
+                            // Both bombs given away is as good as the answer
+                            daily.guesses = vec![b1, b2];
+                            daily.finish(DailyOutcome::GaveUp, game.hints_used)?;
+                        }
+                        hint = Some(((b1, b2), 2));
+                        title = String::from("Hint: both bombs revealed");
+                        term.write_msglog(&format!(
+                            "Hint: the other bomb goes at {}, which clears the rest.",
+                            b2
+                        ))?;
+                    }
+                    Some(_) => {
+                        term.write_msglog("Both bombs are already revealed, no more hints.")?
+                    }
+                    None => {
+                        let cancel = CancelToken::new().with_timeout(timeout);
+                        term.write_msglog("Looking for a hint...\nPress <Esc> to cancel.")?;
+                        term.commit()?;
+                        let receiver =
+                            spawn_solver(game.board.clone(), Search::Hint, threads, cancel.clone());
+                        let outcome =
+                            wait_for_solver(&mut term, &receiver, Search::Hint, &cancel)?;
+                        let result = match outcome {
+                            Some(outcome) => outcome.result,
+                            None => return leave_daily(&mut game),
+                        };
+                        match result.solution() {
+                            Some((b1, b2)) => {
+                                // Only give away the first bomb, and let the targets it leaves
+                                // standing show up as '!'
+                                game.board.place_bombs(&[b1]);
+                                game.hints_used += 1;
+                                if let Some(daily) = &mut game.daily {
+                                    daily.guesses = vec![b1];
+                                }
+                                hint = Some(((b1, b2), 1));
+                                solutions = None;
+                                let targets = game.board.targets();
+                                let geometry = game.board.geometry();
+                                let cleared = targets
+                                    .iter()
+                                    .filter(|target| geometry.hits(&b1, target))
+                                    .count();
+                                title = String::from("Hint: one bomb revealed");
+                                if cleared == targets.len() {
+                                    // Nothing left for the other bomb to reveal
+                                    hint = Some(((b1, b2), 2));
+                                    term.write_msglog(&format!(
+                                        "Hint: a bomb at {} clears all {} targets by itself.",
+                                        b1, cleared
+                                    ))?;
+                                } else {
+                                    term.write_msglog(&format!(
+                                        "Hint: a bomb at {} clears {} of {} targets.\nThe \
+                                         targets it leaves standing are shown as '!'.\nPress \
+                                         'H' again to reveal the other bomb.",
+                                        b1,
+                                        cleared,
+                                        targets.len()
+                                    ))?;
+                                }
+                            }
+                            None if result.cancelled => {
+                                term.write_msglog(&format!(
+                                    "Looking for a hint was cancelled.\n{}",
+                                    result
+                                ))?
+                            }
+                            None => term.write_msglog(
+                                "There's no two-bomb solution to hint at. Press 'S' to see why.",
+                            )?,
+                        }
+                    }
+                }
+                term.commit()?;
+            }
+            GameAction::NewPuzzle => {
+                let seed = clock_seed();
+                match generate_unique(&game.board.geometry(), RANDOM_PUZZLE_TARGETS, seed) {
+                    Ok(targets) => {
+                        game.board.set_targets(&targets);
+                        solutions = None;
+                        rating = None;
+                        hint = None;
+                        title = format!("Random puzzle: clear {} targets with 2 bombs", targets.len());
+                        term.write_msglog(&format!(
+                            "New random puzzle from seed {}, with exactly one solution.\nPress \
+                             'H' for a hint, or 'S' to see the answer.",
+                            seed
+                        ))?;
+                    }
+                    Err(e) => term.write_msglog(&e.to_string())?,
+                }
+                term.commit()?;
+            }
+            GameAction::Minimize => {
+                let cancel = CancelToken::new().with_timeout(timeout);
+                let target_count = game.board.targets().len();
+                term.write_msglog("Minimizing...\nPress <Esc> to cancel.")?;
+                term.commit()?;
+                let receiver =
+                    spawn_solver(game.board.clone(), Search::Minimize, threads, cancel.clone());
+                let outcome = wait_for_solver(&mut term, &receiver, Search::Minimize, &cancel)?;
+                let outcome = match outcome {
+                    Some(outcome) => outcome,
+                    None => return leave_daily(&mut game),
+                };
+                match outcome.minimized {
+                    Some(Ok(kept)) => {
+                        game.board.set_targets(&kept);
+                        solutions = None;
+                        rating = None;
+                        hint = None;
+                        title = format!("{} targets left", kept.len());
+                        term.write_msglog(&format!(
+                            "Removed {} of {} targets, the rest keep the same unique solution.",
+                            target_count - kept.len(),
+                            target_count
+                        ))?;
+                    }
+                    Some(Err(_)) if cancel.is_cancelled() => {
+                        term.write_msglog("Minimizing cancelled, the board is unchanged.")?
+                    }
+                    Some(Err(e)) => term.write_msglog(&e.to_string())?,
+                    None => (),
                 }
                 term.commit()?;
             }
-            GameAction::Quit => return Ok(()),
+            GameAction::Cancel | GameAction::Quit => return leave_daily(&mut game),
         }
     }
 }
@@ -1,42 +1,72 @@
 use crate::coord::Coord;
+use crate::geometry::{Geometry, Grid, Topology};
 
 pub struct RenderSettings {
     game_padding: usize,
-    board_size: usize,
+    board_width: usize,
+    board_height: usize,
     board_padding: usize,
+    // whether the cursor wraps around the board edges, for torus boards
+    board_wraps: bool,
+    board_grid: Grid,
 }
 
 impl RenderSettings {
-    pub fn new(game_padding: usize, board_padding: usize, board_size: usize) -> Self {
+    pub fn new(game_padding: usize, board_padding: usize, geometry: &Geometry) -> Self {
         RenderSettings {
             game_padding,
-            board_size,
+            board_width: geometry.width,
+            board_height: geometry.height,
             board_padding,
+            board_wraps: geometry.topology == Topology::Torus,
+            board_grid: geometry.grid,
         }
     }
 
-    pub fn get_board_size(&self) -> usize {
-        self.board_size
+    pub fn get_board_width(&self) -> usize {
+        self.board_width
     }
 
-    pub fn term_coord_to_cell_coord(&self, term_coord: &Coord) -> Coord {
+    pub fn get_board_height(&self) -> usize {
+        self.board_height
+    }
+
+    pub fn get_board_wraps(&self) -> bool {
+        self.board_wraps
+    }
+
+    /// The board cell under `term_coord`, or `None` if it's off the board.
+    pub fn term_coord_to_cell_coord(&self, term_coord: &Coord) -> Option<Coord> {
         let term_rel_coord: Coord = *term_coord - self.get_board_origin();
-        Coord {
-            x: term_rel_coord.x.saturating_sub(1) / 2,
-            y: term_rel_coord.y.saturating_sub(1) / 2,
+        let y = term_rel_coord.y.saturating_sub(1) / 2;
+        let x = match self.board_grid {
+            Grid::Square => term_rel_coord.x.saturating_sub(1) / 2,
+            // Hexes are 4 chars wide, and odd rows start 2 chars further right
+            Grid::Hex => term_rel_coord.x.checked_sub((y % 2) * 2)? / 4,
+        };
+        let cell_coord = Coord { x, y };
+        if cell_coord.x < self.board_width && cell_coord.y < self.board_height {
+            Some(cell_coord)
+        } else {
+            None
         }
     }
 
     pub fn cell_coord_to_term_coord(&self, cell_coord: &Coord) -> Coord {
+        let x = match self.board_grid {
+            Grid::Square => cell_coord.x.saturating_mul(2).saturating_add(1),
+            Grid::Hex => cell_coord.x * 4 + (cell_coord.y % 2) * 2 + 2,
+        };
         let term_rel_coord = Coord {
-            x: cell_coord.x.saturating_mul(2).saturating_add(1),
+            x,
             y: cell_coord.y.saturating_mul(2).saturating_add(1),
         };
         self.get_board_origin() + term_rel_coord
     }
 
     pub fn get_rendered_board_height(&self) -> usize {
-        (1 + (self.board_size + 2)) as usize
+        // a separator line above each row, plus the bottom border
+        (self.board_height * 2) + 1
     }
 
     pub fn get_title_origin(&self) -> Coord {
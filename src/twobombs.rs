@@ -1,88 +1,178 @@
 use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::str::FromStr;
 
+use chrono::NaiveDate;
+
+use crate::bitboard::BitBoard;
+use crate::certificate::{find_certificate, Certificate};
+use crate::control::{CancelToken, ProgressFn};
 use crate::coord::Coord;
+use crate::coverage::{find_max_coverage, Coverage};
+use crate::daily::{self, DailyGame, Stats};
+use crate::difficulty::{rate, Rating};
 use crate::errors::TBError;
+use crate::geometry::{Geometry, Grid};
+use crate::live::{LiveSolver, Solvability};
+use crate::minimize::minimize;
+use crate::parallel;
+use crate::result::SolveResult;
+use crate::symmetry::{choose_pivot, find_symmetries};
+
+pub fn find_attack_points(targets: &HashSet<Coord>, geometry: &Geometry) -> HashSet<Coord> {
+    geometry.attack_points(targets)
+}
 
-fn abs_difference<T: std::ops::Sub<Output = T> + Ord>(x: T, y: T) -> T {
-    if x < y {
-        y - x
-    } else {
-        x - y
+/// Returns the first bomb pair, in the order [`find_all_bombs`] yields them, that clears all of
+/// `targets`.
+pub fn find_bombs(targets: &HashSet<Coord>, geometry: &Geometry) -> Option<(Coord, Coord)> {
+    find_all_bombs(targets, geometry).next()
+}
+
+/// Lazily enumerates every distinct unordered bomb pair that clears all of `targets`.
+///
+/// Each pair is yielded exactly once, so `(a, b)` and `(b, a)` are never both reported. Every
+/// solver lists solutions in the same order: the first bomb of each pair is the one hitting the
+/// pivot target `choose_pivot` picks, or the earlier one in row-major order if both do, and pairs
+/// come in row-major order of the first bomb, then of the second.
+pub fn find_all_bombs(targets: &HashSet<Coord>, geometry: &Geometry) -> AllBombs {
+    // Whichever pair clears the board, at least one of its bombs has to hit this target, so
+    // only its attack points need to be tried as the first bomb.
+    let pivot = choose_pivot(targets, geometry, &find_symmetries(targets, geometry));
+    let mut b1_attack_points: Vec<Coord> =
+        find_attack_points(&pivot.iter().cloned().collect(), geometry).into_iter().collect();
+    b1_attack_points.sort();
+    AllBombs {
+        targets: targets.clone(),
+        geometry: geometry.clone(),
+        pivot,
+        b1_attack_points: b1_attack_points.into_iter(),
+        b1: None,
     }
 }
 
-fn find_attack_points(targets: &HashSet<Coord>, dim: usize) -> HashSet<Coord> {
-    let mut attack_points = HashSet::new();
-    for target in targets {
-        for i in 0..dim {
-            attack_points.insert(Coord::from((target.x, i)));
-            attack_points.insert(Coord::from((i, target.y)));
-            if target.x + i < dim {
-                if target.y + i < dim {
-                    attack_points.insert(Coord::from((target.x + i, target.y + i)));
-                }
-                if target.y > i {
-                    attack_points.insert(Coord::from((target.x + i, target.y - i)));
-                }
-            }
-            if target.x > i {
-                if target.y + i < dim {
-                    attack_points.insert(Coord::from((target.x - i, target.y + i)));
+/// Iterator over all two-bomb solutions, created by [`find_all_bombs`].
+pub struct AllBombs {
+    targets: HashSet<Coord>,
+    geometry: Geometry,
+    pivot: Option<Coord>,
+    b1_attack_points: std::vec::IntoIter<Coord>,
+    b1: Option<(Coord, std::vec::IntoIter<Coord>)>,
+}
+
+impl AllBombs {
+    fn next_b1(&mut self) -> Option<(Coord, std::vec::IntoIter<Coord>)> {
+        let b1_attack_point = self.b1_attack_points.next()?;
+        let b1_remaining_targets = test_explode(&b1_attack_point, &self.targets, &self.geometry);
+        log::debug!("bomb 1 ({}) missed {} targets", b1_attack_point, b1_remaining_targets.len());
+        let mut b2_attack_points: Vec<Coord> = if b1_remaining_targets.is_empty() {
+            // The first bomb cleared everything by itself, so the second may go anywhere
+            self.geometry.cells().collect()
+        } else {
+            find_attack_points(&b1_remaining_targets, &self.geometry)
+                .into_iter()
+                .filter(|b2| test_explode(b2, &b1_remaining_targets, &self.geometry).is_empty())
+                .collect()
+        };
+        b2_attack_points.sort();
+        Some((b1_attack_point, b2_attack_points.into_iter()))
+    }
+
+    fn is_duplicate(&self, b1: &Coord, b2: &Coord) -> bool {
+        if b1 == b2 {
+            return true;
+        }
+        // When both bombs hit the pivot, the pair also turns up with the bombs swapped, so
+        // only keep the ordering where the first bomb comes first in row-major order
+        match &self.pivot {
+            Some(pivot) if self.geometry.hits(b2, pivot) => (b2.y, b2.x) < (b1.y, b1.x),
+            _ => false,
+        }
+    }
+}
+
+impl Iterator for AllBombs {
+    type Item = (Coord, Coord);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (b1, b2) = match &mut self.b1 {
+                Some((b1, b2_attack_points)) => (*b1, b2_attack_points.next()),
+                None => {
+                    self.b1 = Some(self.next_b1()?);
+                    continue;
                 }
-                if target.y > i {
-                    attack_points.insert(Coord::from((target.x - i, target.y - i)));
+            };
+            match b2 {
+                Some(b2) if !self.is_duplicate(&b1, &b2) => {
+                    log::debug!("Winner! Bomb 1: {} Bomb2: {}", b1, b2);
+                    return Some((b1, b2));
                 }
+                Some(_) => (),
+                None => self.b1 = None,
             }
         }
     }
-    attack_points
 }
 
-fn find_bombs(targets: &HashSet<Coord>, dim: usize) -> Option<(Coord, Coord)> {
-    let b1_attack_points = find_attack_points(targets, dim);
-    for b1_attack_point in b1_attack_points {
-        let b1_remaining_targets = test_explode(&b1_attack_point, targets);
-        log::debug!("bomb 1 ({}) missed {} targets", b1_attack_point, b1_remaining_targets.len());
-        let b2_attack_points = find_attack_points(&b1_remaining_targets, dim);
-        for b2_attack_point in b2_attack_points {
-            let b2_remaining_targets = test_explode(&b2_attack_point, &b1_remaining_targets);
-            log::debug!("bomb 2 ({}) missed {} targets", b2_attack_point, b2_remaining_targets.len());
-            if b2_remaining_targets.is_empty() {
-                log::debug!("Winner! Bomb 1: {} Bomb2: {}", b1_attack_point, b2_attack_point);
-                return Some((b1_attack_point, b2_attack_point));
-            }
-        }
+/// Finds the smallest number of bombs, up to `max_bombs`, that clears all of `targets`.
+///
+/// Returns a witness placement with that many bombs, or `None` if even `max_bombs` bombs aren't
+/// enough or `cancel` was cancelled first.
+pub fn find_min_bombs(
+    targets: &HashSet<Coord>,
+    geometry: &Geometry,
+    max_bombs: usize,
+    cancel: &CancelToken,
+) -> Option<Vec<Coord>> {
+    (0..=max_bombs).find_map(|bomb_count| {
+        log::debug!("trying {} bombs", bomb_count);
+        find_n_bombs(targets, geometry, bomb_count, cancel)
+    })
+}
+
+/// Finds a placement of at most `bomb_count` bombs that clears all of `targets`.
+///
+/// Bombs are tried in row-major order, and each one has to hit the first target in row-major
+/// order that the bombs before it left standing, so the same targets always give the same
+/// placement.
+pub fn find_n_bombs(
+    targets: &HashSet<Coord>,
+    geometry: &Geometry,
+    bomb_count: usize,
+    cancel: &CancelToken,
+) -> Option<Vec<Coord>> {
+    // Whatever the placement, some bomb has to hit this target, so it's the only one we need to
+    // branch on at this level
+    let pivot = match targets.iter().min() {
+        Some(pivot) => *pivot,
+        None => return Some(Vec::new()),
+    };
+    if bomb_count == 0 || cancel.is_cancelled() {
+        return None;
     }
-    /*
-    for x1 in 0..self.size {
-        for y1 in 0..self.size {
-            for x2 in 0..self.size {
-                for y2 in 0..self.size {
-                    let bomb1 = Coord::from((x1, y1));
-                    let bomb2 = Coord::from((x2, y2));
-                    if self.test_solution(&bomb1, &bomb2) {
-                        self.set(&bomb1, TBCell::Bomb)?;
-                        self.set(&bomb2, TBCell::Bomb)?;
-                        return Ok(Some((bomb1, bomb2)));
-                    }
-                }
-            }
+    let mut pivot_attack_points: Vec<Coord> =
+        find_attack_points(&std::iter::once(pivot).collect(), geometry).into_iter().collect();
+    pivot_attack_points.sort();
+    for attack_point in pivot_attack_points {
+        let remaining_targets = test_explode(&attack_point, targets, geometry);
+        if let Some(mut bombs) = find_n_bombs(&remaining_targets, geometry, bomb_count - 1, cancel) {
+            bombs.insert(0, attack_point);
+            return Some(bombs);
         }
     }
     None
-    */
-    None
 }
 
-fn test_explode(attack_point: &Coord, targets: &HashSet<Coord>) -> HashSet<Coord> {
+/// The targets left standing after a bomb at `attack_point` goes off.
+pub fn test_explode(
+    attack_point: &Coord,
+    targets: &HashSet<Coord>,
+    geometry: &Geometry,
+) -> HashSet<Coord> {
     targets
         .iter()
-        .filter(|t| {
-            t.x != attack_point.x
-                && t.y != attack_point.y
-                && (abs_difference(t.x, attack_point.x) != abs_difference(t.y, attack_point.y))
-        })
+        .filter(|t| !geometry.hits(attack_point, t))
         .cloned()
         .collect()
 }
@@ -142,38 +232,105 @@ fn test_solution(&self, bomb1: &Coord, bomb2: &Coord) -> bool {
 pub enum TBCell {
     Bomb,
     Target,
+    /// A bomb placed on a target, which stays a target once the bomb is cleared again
+    BombedTarget,
+    Wall,
+}
+
+impl TBCell {
+    pub fn is_target(self) -> bool {
+        self == TBCell::Target || self == TBCell::BombedTarget
+    }
+
+    pub fn is_bomb(self) -> bool {
+        self == TBCell::Bomb || self == TBCell::BombedTarget
+    }
 }
 
 impl fmt::Display for TBCell {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            TBCell::Bomb => write!(f, "X"),
+            TBCell::Bomb | TBCell::BombedTarget => write!(f, "X"),
             TBCell::Target => write!(f, "O"),
+            TBCell::Wall => write!(f, "#"),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TBBoard {
-    pub cells: HashMap<Coord, TBCell>,
-    pub size: usize,
+    // Only changed through set(), clear() and the like, which keep `live` up to date
+    cells: HashMap<Coord, TBCell>,
+    geometry: Geometry,
+    live: LiveSolver,
+}
+
+// Plain text board format: one line per row, 'O' for a target, 'X' for a bomb, '#' for a wall,
+// and '.' for an empty cell
+impl FromStr for TBBoard {
+    type Err = TBError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<&str> = s
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        let mut board = TBBoard::new_with_geometry(Geometry::queen(width, rows.len()));
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(TBError::BadBoard(format!(
+                    "row {} has {} cells, expected {}",
+                    y,
+                    row.chars().count(),
+                    width
+                )));
+            }
+            for (x, c) in row.chars().enumerate() {
+                let coord = Coord { x, y };
+                let cell = match c {
+                    'O' | 'o' => TBCell::Target,
+                    'X' | 'x' => TBCell::Bomb,
+                    '#' => TBCell::Wall,
+                    '.' => continue,
+                    _ => {
+                        return Err(TBError::BadBoard(format!(
+                            "unexpected '{}' at {}",
+                            c, coord
+                        )))
+                    }
+                };
+                board.cells.insert(coord, cell);
+            }
+        }
+        // Filled in directly rather than through set(), so the whole board is solved once
+        // instead of once per target
+        board.live = LiveSolver::new(&board.targets(), &board.geometry());
+        Ok(board)
+    }
 }
 
 // Box drawing chars from https://en.wikipedia.org/wiki/Box-drawing_character
 impl fmt::Display for TBBoard {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let size = self.get_size();
         let indent: String = "    ".to_string();
 
         // Write column numbers
         let mut line = indent.clone();
-        for x in 0..size {
-            line.push(' ');
-            line.push_str(x.to_string().as_str());
+        for x in 0..self.get_width() {
+            match self.geometry.grid {
+                Grid::Square => {
+                    line.push(' ');
+                    line.push_str(x.to_string().as_str());
+                }
+                // Line the numbers up with the middle of the even row cells
+                Grid::Hex => line.push_str(&format!("{: >3} ", x)),
+            }
         }
         writeln!(f, "{}", line)?;
 
-        for y in 0..size {
+        for y in 0..self.get_height() {
             if y == 0 {
                 // Draw the top of the board
                 line = indent.clone();
@@ -182,7 +339,7 @@ impl fmt::Display for TBBoard {
             } else {
                 // Draw a row separator
                 line = indent.clone();
-                line.push_str(self.render_board_row_sep().as_str());
+                line.push_str(self.render_board_row_sep(y).as_str());
                 writeln!(f, "{}", line)?;
             }
 
@@ -202,10 +359,13 @@ impl fmt::Display for TBBoard {
 
 impl TBBoard {
     pub fn render_board_top(&self) -> String {
+        if self.geometry.grid == Grid::Hex {
+            return " / \\".repeat(self.get_width());
+        }
         // starting and ending char, a char for each board cell, and a char separating each board
         // cell
-        let mut line = String::with_capacity(2 + (self.get_size() * 2) - 1);
-        for x in 0..self.get_size() {
+        let mut line = String::with_capacity(2 + (self.get_width() * 2) - 1);
+        for x in 0..self.get_width() {
             if x == 0 {
                 line.push('╭');
             } else {
@@ -218,10 +378,15 @@ impl TBBoard {
     }
 
     pub fn render_board_bottom(&self) -> String {
+        if self.geometry.grid == Grid::Hex {
+            // The bottom edges of the last row, shifted along with it if it's an odd row
+            let indent = if self.get_height().is_multiple_of(2) { "  " } else { "" };
+            return format!("{}{}", indent, " \\ /".repeat(self.get_width()));
+        }
         // starting and ending char, a char for each board cell, and a char separating each board
         // cell
-        let mut line = String::with_capacity(2 + (self.get_size() * 2) - 1);
-        for x in 0..self.get_size() {
+        let mut line = String::with_capacity(2 + (self.get_width() * 2) - 1);
+        for x in 0..self.get_width() {
             if x == 0 {
                 line.push('╰');
             } else {
@@ -233,25 +398,59 @@ impl TBBoard {
         line
     }
 
+    fn render_cell(&self, coord: &Coord, bombs: &[Coord], geometry: &Geometry) -> char {
+        match self.fetch(coord) {
+            Some(TBCell::Bomb) | Some(TBCell::BombedTarget) => 'X',
+            // Targets left standing by the placed bombs
+            Some(TBCell::Target)
+                if !bombs.is_empty() && !bombs.iter().any(|bomb| geometry.hits(bomb, coord)) =>
+            {
+                '!'
+            }
+            Some(TBCell::Target) => 'O',
+            Some(TBCell::Wall) => '█',
+            // Show how far the placed bombs' blasts reach
+            None if bombs.iter().any(|bomb| geometry.hits(bomb, coord)) => '·',
+            None => ' ',
+        }
+    }
+
     pub fn render_board_row(&self, y: usize) -> String {
+        let bombs = self.bombs();
+        let geometry = self.geometry();
+        if self.geometry.grid == Grid::Hex {
+            // Each hex is drawn 4 chars wide, with odd rows shifted right by half of that
+            let mut line = String::from(if y.is_multiple_of(2) { "|" } else { "  |" });
+            for x in 0..self.get_width() {
+                line.push(' ');
+                line.push(self.render_cell(&Coord { x, y }, &bombs, &geometry));
+                line.push_str(" |");
+            }
+            return line;
+        }
         // starting and ending char, a char for each board cell, and a char separating each board
         // cell
-        let mut line = String::with_capacity(2 + (self.get_size() * 2) - 1);
-        for x in 0..self.get_size() {
+        let mut line = String::with_capacity(2 + (self.get_width() * 2) - 1);
+        for x in 0..self.get_width() {
             line.push('│');
-            match self.fetch(&Coord { x, y }) {
-                Some(TBCell::Bomb) => line.push('X'),
-                Some(TBCell::Target) => line.push('O'),
-                None => line.push(' '),
-            }
+            line.push(self.render_cell(&Coord { x, y }, &bombs, &geometry));
         }
         line.push('│');
         line
     }
 
-    pub fn render_board_row_sep(&self) -> String {
-        let mut line = String::with_capacity(2 + (self.get_size() * 2) - 1);
-        for x in 0..self.get_size() {
+    /// The line between row `y` and the row above it.
+    pub fn render_board_row_sep(&self, y: usize) -> String {
+        if self.geometry.grid == Grid::Hex {
+            // Where the bottom edges of one row meet the top edges of the next
+            return if y.is_multiple_of(2) {
+                format!("{} /", " / \\".repeat(self.get_width()))
+            } else {
+                format!("{} \\", " \\ /".repeat(self.get_width()))
+            };
+        }
+        let mut line = String::with_capacity(2 + (self.get_width() * 2) - 1);
+        for x in 0..self.get_width() {
             if x == 0 {
                 line.push('├');
             } else {
@@ -263,8 +462,25 @@ impl TBBoard {
         line
     }
 
-    pub fn get_size(&self) -> usize {
-        self.size
+    pub fn get_width(&self) -> usize {
+        self.geometry.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.geometry.height
+    }
+
+    /// The board's geometry, including any walls placed on it.
+    pub fn geometry(&self) -> Geometry {
+        Geometry {
+            walls: self.walls(),
+            ..self.geometry.clone()
+        }
+    }
+
+    /// Every cell that isn't empty.
+    pub fn cells(&self) -> &HashMap<Coord, TBCell> {
+        &self.cells
     }
 
     pub fn fetch(&self, coord: &Coord) -> Option<&TBCell> {
@@ -272,8 +488,9 @@ impl TBBoard {
     }
 
     pub fn set(&mut self, coord: &Coord, new_state: TBCell) -> Result<TBCell, TBError> {
-        if coord.x <= self.size && coord.y <= self.size {
-            self.cells.insert(*coord, new_state);
+        if self.geometry.contains(coord) {
+            let old_state = self.cells.insert(*coord, new_state);
+            self.update_live(coord, old_state, Some(new_state));
             Ok(new_state)
         } else {
             Err(TBError::InvalidCellPosition(*coord))
@@ -281,11 +498,83 @@ impl TBBoard {
     }
 
     pub fn clear(&mut self, coord: &Coord) {
-        self.cells.remove(coord);
+        let old_state = self.cells.remove(coord);
+        self.update_live(coord, old_state, None);
+    }
+
+    fn update_live(&mut self, coord: &Coord, old_state: Option<TBCell>, new_state: Option<TBCell>) {
+        let is_wall = |state| state == Some(TBCell::Wall);
+        if is_wall(old_state) != is_wall(new_state) {
+            // Walls change where every blast goes, so start over
+            self.live = LiveSolver::new(&self.targets(), &self.geometry());
+        } else {
+            let is_target = |state: Option<TBCell>| state.is_some_and(TBCell::is_target);
+            if is_target(old_state) && !is_target(new_state) {
+                self.live.remove(coord);
+            } else if is_target(new_state) && !is_target(old_state) {
+                self.live.add(coord);
+            }
+        }
+    }
+
+    /// Whether the targets on the board can be cleared with two bombs, kept up to date as cells
+    /// are changed.
+    pub fn solvability(&self) -> Solvability {
+        self.live.solvability()
+    }
+
+    /// How hard the board is to solve, or `None` if two bombs can't clear it.
+    pub fn rate(&self, threads: usize) -> Option<Rating> {
+        rate(&self.targets(), &self.geometry(), threads)
+    }
+
+    /// Removes every target the board's unique solution doesn't depend on, returning how many
+    /// went. Fails if the board doesn't have exactly one solution, or if `cancel` is cancelled
+    /// first, leaving the board as it was.
+    pub fn minimize(
+        &mut self,
+        threads: usize,
+        cancel: &CancelToken,
+        progress: ProgressFn,
+    ) -> Result<usize, TBError> {
+        let targets = self.targets();
+        let kept = minimize(&targets, &self.geometry(), threads, cancel, progress)?;
+        self.set_targets(&kept);
+        Ok(targets.len() - kept.len())
+    }
+
+    pub fn set_geometry(&mut self, geometry: Geometry) {
+        self.geometry = geometry;
+        self.live = LiveSolver::new(&self.targets(), &self.geometry());
+    }
+
+    /// Replaces every target on the board with `targets`, clearing any bombs. Walls stay put.
+    pub fn set_targets(&mut self, targets: &HashSet<Coord>) {
+        self.cells.retain(|_, cell| *cell == TBCell::Wall);
+        for target in targets {
+            self.cells.insert(*target, TBCell::Target);
+        }
+        self.live = LiveSolver::new(targets, &self.geometry());
+    }
+
+    /// The board in the text format it's parsed from, leaving out any bombs.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for y in 0..self.get_height() {
+            for x in 0..self.get_width() {
+                text.push(match self.fetch(&Coord { x, y }) {
+                    Some(TBCell::Target) | Some(TBCell::BombedTarget) => 'O',
+                    Some(TBCell::Wall) => '#',
+                    Some(TBCell::Bomb) | None => '.',
+                });
+            }
+            text.push('\n');
+        }
+        text
     }
 
     pub fn mark_target(&mut self, coord: &Coord) {
-        if self.cells.get(coord).is_some() {
+        if self.cells.contains_key(coord) {
             self.clear(coord);
         } else {
             self.set(coord, TBCell::Target)
@@ -293,46 +582,152 @@ impl TBBoard {
         }
     }
 
+    pub fn mark_wall(&mut self, coord: &Coord) {
+        if self.cells.get(coord) == Some(&TBCell::Wall) {
+            self.clear(coord);
+        } else {
+            self.set(coord, TBCell::Wall)
+                .expect("Invalid board coordinate for wall!");
+        }
+    }
+
     pub fn init(&mut self) {
         // reset the board
         self.cells.clear();
-        self.cells.reserve((self.size * self.size) as usize);
+        self.cells.reserve(self.get_width() * self.get_height());
+        self.live = LiveSolver::new(&HashSet::new(), &self.geometry);
     }
 
     pub fn new_anysize(size: usize) -> Self {
+        TBBoard::new_with_geometry(Geometry::queen(size, size))
+    }
+
+    pub fn new_with_geometry(geometry: Geometry) -> Self {
         let mut _self = TBBoard {
             cells: HashMap::new(),
-            size,
+            live: LiveSolver::new(&HashSet::new(), &geometry),
+            geometry,
         };
         _self.init();
         _self
     }
 
-    pub fn find_bombs(&mut self) -> Option<(Coord, Coord)> {
-        let targets: HashSet<Coord> = self.cells.keys().cloned().collect();
-        let bombs = find_bombs(&targets, self.get_size());
-        if let Some((b1, b2)) = &bombs {
-            self.set(b1, TBCell::Bomb)
-                .expect("Invalid board coordinate for bomb!");
-            self.set(b2, TBCell::Bomb)
+    pub fn targets(&self) -> HashSet<Coord> {
+        self.cells
+            .iter()
+            .filter(|(_, cell)| cell.is_target())
+            .map(|(coord, _)| *coord)
+            .collect()
+    }
+
+    pub fn bombs(&self) -> Vec<Coord> {
+        self.cells
+            .iter()
+            .filter(|(_, cell)| cell.is_bomb())
+            .map(|(coord, _)| *coord)
+            .collect()
+    }
+
+    pub fn walls(&self) -> HashSet<Coord> {
+        self.cells
+            .iter()
+            .filter(|(_, cell)| **cell == TBCell::Wall)
+            .map(|(coord, _)| *coord)
+            .collect()
+    }
+
+    pub fn clear_bombs(&mut self) {
+        self.cells.retain(|_, cell| *cell != TBCell::Bomb);
+        // Targets that had a bomb on them are still targets
+        for cell in self.cells.values_mut() {
+            if *cell == TBCell::BombedTarget {
+                *cell = TBCell::Target;
+            }
+        }
+    }
+
+    pub fn place_bombs(&mut self, bombs: &[Coord]) {
+        self.clear_bombs();
+        for bomb in bombs {
+            let cell = if self.fetch(bomb) == Some(&TBCell::Target) {
+                TBCell::BombedTarget
+            } else {
+                TBCell::Bomb
+            };
+            self.set(bomb, cell)
                 .expect("Invalid board coordinate for bomb!");
         }
+    }
+
+    /// Finds and places the first solution in the order [`TBBoard::find_all_bombs`] lists them.
+    pub fn find_bombs(
+        &mut self,
+        threads: usize,
+        cancel: &CancelToken,
+        progress: ProgressFn,
+    ) -> SolveResult {
+        let bitboard = BitBoard::new(&self.targets(), &self.geometry());
+        let result = parallel::find_bombs(&bitboard, threads, cancel, progress);
+        if let Some((b1, b2)) = result.solution() {
+            self.place_bombs(&[b1, b2]);
+        }
+
+        result
+    }
+
+    pub fn find_certificate(&self) -> Option<Certificate> {
+        find_certificate(&self.targets(), &self.geometry())
+    }
+
+    pub fn find_min_bombs(&mut self, max_bombs: usize, cancel: &CancelToken) -> Option<Vec<Coord>> {
+        let bombs = find_min_bombs(&self.targets(), &self.geometry(), max_bombs, cancel);
+        if let Some(bombs) = &bombs {
+            self.place_bombs(bombs);
+        }
 
         bombs
     }
 
-    #[allow(dead_code)]
+    /// Places up to `bomb_count` bombs so they destroy as many targets as possible.
+    pub fn find_max_coverage(&mut self, bomb_count: usize) -> Coverage {
+        let coverage = find_max_coverage(&self.targets(), &self.geometry(), bomb_count);
+        self.place_bombs(&coverage.bombs);
+
+        coverage
+    }
+
+    /// Finds every pair of bombs that clears the board, in the same order as the lazy
+    /// [`find_all_bombs`] lists them, so either solver gives the same answers.
+    pub fn find_all_bombs(
+        &self,
+        threads: usize,
+        cancel: &CancelToken,
+        progress: ProgressFn,
+    ) -> SolveResult {
+        let bitboard = BitBoard::new(&self.targets(), &self.geometry());
+        parallel::find_all_bombs(&bitboard, threads, cancel, progress)
+    }
+
     pub fn new() -> Self {
         TBBoard::new_anysize(3)
     }
 }
 
+impl Default for TBBoard {
+    fn default() -> Self {
+        TBBoard::new()
+    }
+}
+
 pub struct TBGame {
     pub board: TBBoard,
+    /// Hints given so far this session
+    pub hints_used: usize,
+    /// Set when playing the daily puzzle
+    pub daily: Option<DailyGame>,
 }
 
 impl TBGame {
-    #[allow(dead_code)]
     pub fn get_board(&self) -> &TBBoard {
         &self.board
     }
@@ -340,11 +735,71 @@ impl TBGame {
     pub fn new_anysize(size: usize) -> Self {
         TBGame {
             board: TBBoard::new_anysize(size),
+            hints_used: 0,
+            daily: None,
+        }
+    }
+
+    pub fn new_with_geometry(geometry: Geometry) -> Self {
+        TBGame {
+            board: TBBoard::new_with_geometry(geometry),
+            hints_used: 0,
+            daily: None,
         }
     }
 
-    #[allow(dead_code)]
+    /// The daily puzzle for `date`, with how it goes recorded in `stats`.
+    pub fn new_daily(date: NaiveDate, stats: Stats) -> Result<Self, TBError> {
+        let mut board = TBBoard::new_with_geometry(daily::geometry());
+        board.set_targets(&daily::puzzle(date)?);
+        Ok(TBGame {
+            board,
+            hints_used: 0,
+            daily: Some(DailyGame::new(date, stats)),
+        })
+    }
+
     pub fn new() -> Self {
         TBGame::new_anysize(3)
     }
 }
+
+impl Default for TBGame {
+    fn default() -> Self {
+        TBGame::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::no_progress;
+
+    #[test]
+    fn solvers_list_solutions_in_the_same_order() {
+        // Symmetric under the board's rotations and reflections, so the threaded solvers only
+        // search part of it and map the rest back
+        let mut board: TBBoard = "\
+            .......\n\
+            .O...O.\n\
+            .......\n\
+            ...O...\n\
+            .......\n\
+            .O...O.\n\
+            .......\n"
+            .parse()
+            .unwrap();
+        let targets = board.targets();
+        let geometry = board.geometry();
+        assert!(find_symmetries(&targets, &geometry).len() > 1);
+
+        let expected: Vec<(Coord, Coord)> = find_all_bombs(&targets, &geometry).collect();
+        assert!(expected.len() > 1);
+        for threads in [1, 2, 3, 8] {
+            let result = board.find_all_bombs(threads, &CancelToken::new(), &no_progress);
+            assert_eq!(result.solutions, expected, "{} threads", threads);
+            let result = board.find_bombs(threads, &CancelToken::new(), &no_progress);
+            assert_eq!(result.solution(), expected.first().copied(), "{} threads", threads);
+        }
+    }
+}